@@ -31,6 +31,19 @@ pub struct FunctionDecleration {
     pub name: String,
     pub params: Vec<String>,
     pub block: Node,
+    pub generator: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnumVariant {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnumDecleration {
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -38,6 +51,7 @@ pub struct Lambda {
     pub id: String,
     pub params: Vec<String>,
     pub block: Node,
+    pub generator: bool,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -57,6 +71,26 @@ pub struct Index {
     pub key: String,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Subscript {
+    pub target: Node,
+    pub index: Node,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SetSubscriptExpr {
+    pub target: Node,
+    pub index: Node,
+    pub value: Node,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ForIn {
+    pub variable: String,
+    pub iterable: Node,
+    pub block: Node,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct SetPropertyExpr {
     pub target: Node,
@@ -64,6 +98,33 @@ pub struct SetPropertyExpr {
     pub value: Node,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum Pattern {
+    Literal(Node),
+    Identifier(String),
+    Wildcard,
+    Alternative(Vec<Pattern>),
+    Object(Vec<(String, Pattern)>),
+    Variant {
+        enum_name: String,
+        variant: String,
+        fields: Option<Vec<Pattern>>,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Node>,
+    pub body: Node,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Match {
+    pub subject: Node,
+    pub arms: Vec<MatchArm>,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Node {
     Number(f64),
@@ -74,18 +135,107 @@ pub enum Node {
     Block(Vec<Node>),
     Lambda(Box<Lambda>),
     Object(Box<Object>),
+    List(Vec<Node>),
     None,
     Index(Box<Index>),
+    Subscript(Box<Subscript>),
     FunctionDecleration(Box<FunctionDecleration>),
+    EnumDecleration(Box<EnumDecleration>),
     FunctionCall(Box<FunctionCall>),
     VariabeDecleration(Box<VariabeDecleration>),
     AssignmentExpr(Box<AssignmentExpr>),
     SetPropertyExpr(Box<SetPropertyExpr>),
+    SetSubscriptExpr(Box<SetSubscriptExpr>),
     BinOperator(Box<BinOperator>),
     UnaryOperator(Box<UnaryOperator>),
+    Match(Box<Match>),
+    ForIn(Box<ForIn>),
+    Yield(Box<Node>),
     Expression(Box<Node>),
 }
 
+impl Match {
+    pub fn contains_yield_in_arms(&self) -> bool {
+        self.arms.iter().any(|arm| {
+            arm.body.contains_yield() || arm.guard.as_ref().is_some_and(Node::contains_yield)
+        })
+    }
+}
+
+impl Node {
+    /// Whether a `yield` statement is reachable from this node without entering another function.
+    pub fn contains_yield(&self) -> bool {
+        match self {
+            Node::Yield(_) => true,
+            Node::Compound(nodes) | Node::Block(nodes) | Node::List(nodes) => {
+                nodes.iter().any(Node::contains_yield)
+            }
+            Node::Expression(node) => node.contains_yield(),
+            Node::ForIn(node) => node.iterable.contains_yield() || node.block.contains_yield(),
+            Node::Match(node) => node.subject.contains_yield() || node.contains_yield_in_arms(),
+            Node::VariabeDecleration(node) => node.value.as_ref().is_some_and(Node::contains_yield),
+            Node::AssignmentExpr(node) => node.value.contains_yield(),
+            Node::SetPropertyExpr(node) => {
+                node.target.contains_yield() || node.value.contains_yield()
+            }
+            Node::SetSubscriptExpr(node) => {
+                node.target.contains_yield()
+                    || node.index.contains_yield()
+                    || node.value.contains_yield()
+            }
+            Node::BinOperator(node) => node.left.contains_yield() || node.right.contains_yield(),
+            Node::UnaryOperator(node) => node.expression.contains_yield(),
+            Node::FunctionCall(node) => {
+                node.function.contains_yield() || node.arguments.iter().any(Node::contains_yield)
+            }
+            Node::Index(node) => node.target.contains_yield(),
+            Node::Subscript(node) => node.target.contains_yield() || node.index.contains_yield(),
+            Node::Object(node) => node.values.values().any(Node::contains_yield),
+            _ => false,
+        }
+    }
+
+    /// Collects the `_` pipeline placeholders in this expression, without
+    /// entering lambdas where `_` can be a parameter.
+    pub fn placeholders<'a>(&'a mut self, found: &mut Vec<&'a mut Node>) {
+        if matches!(self, Node::Identifier(iden) if iden == "_") {
+            found.push(self);
+            return;
+        }
+        match self {
+            Node::List(nodes) => {
+                for node in nodes {
+                    node.placeholders(found)
+                }
+            }
+            Node::Expression(node) => node.placeholders(found),
+            Node::BinOperator(node) => {
+                node.left.placeholders(found);
+                node.right.placeholders(found)
+            }
+            Node::UnaryOperator(node) => node.expression.placeholders(found),
+            Node::FunctionCall(node) => {
+                node.function.placeholders(found);
+                for argument in &mut node.arguments {
+                    argument.placeholders(found)
+                }
+            }
+            Node::Index(node) => node.target.placeholders(found),
+            Node::Subscript(node) => {
+                node.target.placeholders(found);
+                node.index.placeholders(found)
+            }
+            Node::Object(node) => {
+                for value in node.values.values_mut() {
+                    value.placeholders(found)
+                }
+            }
+            Node::Match(node) => node.subject.placeholders(found),
+            _ => {}
+        }
+    }
+}
+
 impl fmt::Display for BinOperator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(&format!("{:?}", self))
@@ -103,15 +253,18 @@ impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(&match self {
             Node::Number(num) => num.to_string(),
-            Node::String(string) => format!("'{}'", string.to_string()),
+            Node::String(string) => format!("'{}'", string),
             Node::Boolean(boolean) => boolean.to_string(),
             Node::Identifier(iden) => iden.to_string(),
-            Node::Compound(statments) => format!("[{}]", join_nodes(&statments)),
-            Node::Block(block) => format!("[{}]", join_nodes(&block)),
+            Node::Compound(statments) => format!("[{}]", join_nodes(statments)),
+            Node::Block(block) => format!("[{}]", join_nodes(block)),
             Node::Lambda(_) => String::from("Lambda"),
             Node::Object(_) => String::from("Object"),
+            Node::List(items) => format!("[{}]", join_nodes(items)),
             Node::Index(index) => format!("{}", index),
+            Node::Subscript(subscript) => format!("{}[{}]", subscript.target, subscript.index),
             Node::FunctionDecleration(_) => String::from("FunctionDecleration"),
+            Node::EnumDecleration(_) => String::from("EnumDecleration"),
             Node::FunctionCall(function_call) => format!(
                 "{}({})",
                 function_call.function,
@@ -131,6 +284,10 @@ impl fmt::Display for Node {
                 "{}.{} = {};",
                 set_property.target, set_property.key, set_property.value
             ),
+            Node::SetSubscriptExpr(set_subscript) => format!(
+                "{}[{}] = {};",
+                set_subscript.target, set_subscript.index, set_subscript.value
+            ),
             Node::BinOperator(bin_operation) => format!(
                 "{} {} {}",
                 bin_operation.left, bin_operation.operator, bin_operation.right
@@ -138,6 +295,9 @@ impl fmt::Display for Node {
             Node::UnaryOperator(unary_operation) => {
                 format!("{}{}", unary_operation.operator, unary_operation.expression)
             }
+            Node::Match(node) => format!("match {} {{ ... }}", node.subject),
+            Node::ForIn(node) => format!("for {} in {} {{ ... }}", node.variable, node.iterable),
+            Node::Yield(node) => format!("yield {};", node),
             Node::Expression(expression) => format!("{}", expression),
             Node::None => String::from("none"),
         })
@@ -149,3 +309,43 @@ impl fmt::Display for Index {
         f.write_str(&format!("{}.{}", &self.target, &self.key))
     }
 }
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&match self {
+            Pattern::Literal(node) => format!("{}", node),
+            Pattern::Identifier(iden) => iden.to_string(),
+            Pattern::Wildcard => String::from("_"),
+            Pattern::Alternative(patterns) => patterns
+                .iter()
+                .map(|pattern| format!("{}", pattern))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Pattern::Object(fields) => format!(
+                "{{ {} }}",
+                fields
+                    .iter()
+                    .map(|(key, pattern)| format!("{}: {}", key, pattern))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Pattern::Variant {
+                enum_name,
+                variant,
+                fields,
+            } => match fields {
+                Some(fields) => format!(
+                    "{}.{}({})",
+                    enum_name,
+                    variant,
+                    fields
+                        .iter()
+                        .map(|pattern| format!("{}", pattern))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                None => format!("{}.{}", enum_name, variant),
+            },
+        })
+    }
+}
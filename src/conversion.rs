@@ -0,0 +1,350 @@
+use crate::{enviroment::Value, interpreter::IResult, misc::NekoError, stdlib::convert::type_of};
+use std::{cell::RefCell, collections::HashMap, convert::TryFrom, hash::BuildHasher, rc::Rc};
+
+/// Conversion from a Neko value into a Rust type.
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Result<Self, NekoError>;
+}
+
+/// Conversion from a Rust type into a Neko value.
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+/// Return types of typed host functions, either a value or a `Result` of one.
+pub trait IntoResult {
+    fn into_result(self) -> IResult;
+}
+
+/// Rust functions taking `FromValue` arguments, callable with a list of Neko values.
+pub trait HostFunction<Args> {
+    fn call(&self, name: &str, args: Vec<Value>) -> IResult;
+}
+
+fn expected(kind: &str, value: &Value) -> NekoError {
+    NekoError::TypeError(format!("Expected {}, got {}", kind, type_of(value)))
+}
+
+impl<T: IntoValue> IntoResult for T {
+    fn into_result(self) -> IResult {
+        Ok(self.into_value())
+    }
+}
+
+impl<T: IntoValue> IntoResult for Result<T, NekoError> {
+    fn into_result(self) -> IResult {
+        self.map(IntoValue::into_value)
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> Result<Self, NekoError> {
+        Ok(value)
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::None
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value) -> Result<Self, NekoError> {
+        match value {
+            Value::Number(num) => Ok(num),
+            other => Err(expected("number", &other)),
+        }
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Value {
+        Value::Number(self)
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: Value) -> Result<Self, NekoError> {
+        f64::from_value(value).map(|num| num as f32)
+    }
+}
+
+impl IntoValue for f32 {
+    fn into_value(self) -> Value {
+        Value::Number(self.into())
+    }
+}
+
+macro_rules! integer {
+    ($($int:ty),*) => {
+        $(
+            impl FromValue for $int {
+                fn from_value(value: Value) -> Result<Self, NekoError> {
+                    let num = f64::from_value(value)?;
+                    // `MAX as f64` rounds up for 64-bit types, so the range is
+                    // checked on the exact `i128` value instead.
+                    match <$int>::try_from(num as i128) {
+                        Ok(int) if num.fract() == 0.0 => Ok(int),
+                        _ => Err(NekoError::TypeError(format!(
+                            "Expected an integer fitting in {}, got {}",
+                            stringify!($int),
+                            num
+                        ))),
+                    }
+                }
+            }
+
+            impl IntoValue for $int {
+                fn into_value(self) -> Value {
+                    Value::Number(self as f64)
+                }
+            }
+        )*
+    };
+}
+
+integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> Result<Self, NekoError> {
+        match value {
+            Value::Boolean(boolean) => Ok(boolean),
+            other => Err(expected("boolean", &other)),
+        }
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Value {
+        Value::Boolean(self)
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Result<Self, NekoError> {
+        match value {
+            Value::String(string) => Ok(string),
+            other => Err(expected("string", &other)),
+        }
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        Value::String(String::from(self))
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Result<Self, NekoError> {
+        match value {
+            Value::None => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        match self {
+            Some(value) => value.into_value(),
+            None => Value::None,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value) -> Result<Self, NekoError> {
+        match value {
+            Value::List(list) => list.borrow().iter().cloned().map(T::from_value).collect(),
+            other => Err(expected("list", &other)),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Value {
+        Value::List(Rc::new(RefCell::new(
+            self.into_iter().map(IntoValue::into_value).collect(),
+        )))
+    }
+}
+
+impl<T: FromValue, S: BuildHasher + Default> FromValue for HashMap<String, T, S> {
+    fn from_value(value: Value) -> Result<Self, NekoError> {
+        match value {
+            Value::Object(obj) => obj
+                .borrow()
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), T::from_value(*value.clone())?)))
+                .collect(),
+            other => Err(expected("object", &other)),
+        }
+    }
+}
+
+impl<T: IntoValue, S: BuildHasher> IntoValue for HashMap<String, T, S> {
+    fn into_value(self) -> Value {
+        Value::Object(Rc::new(RefCell::new(
+            self.into_iter()
+                .map(|(key, value)| (key, Box::new(value.into_value())))
+                .collect(),
+        )))
+    }
+}
+
+macro_rules! tuple {
+    ($len:literal; $($item:ident),+) => {
+        impl<$($item: FromValue),+> FromValue for ($($item,)+) {
+            #[allow(non_snake_case)]
+            fn from_value(value: Value) -> Result<Self, NekoError> {
+                match value {
+                    Value::List(list) if list.borrow().len() == $len => {
+                        let mut items = list.borrow().clone().into_iter();
+                        $(let $item = <$item>::from_value(items.next().unwrap())?;)+
+                        Ok(($($item,)+))
+                    }
+                    Value::List(list) => Err(NekoError::TypeError(format!(
+                        "Expected a list of {} items, got {}",
+                        $len,
+                        list.borrow().len()
+                    ))),
+                    other => Err(expected("list", &other)),
+                }
+            }
+        }
+
+        impl<$($item: IntoValue),+> IntoValue for ($($item,)+) {
+            #[allow(non_snake_case)]
+            fn into_value(self) -> Value {
+                let ($($item,)+) = self;
+                Value::List(Rc::new(RefCell::new(vec![$($item.into_value()),+])))
+            }
+        }
+    };
+}
+
+tuple!(1; A);
+tuple!(2; A, B);
+tuple!(3; A, B, C);
+tuple!(4; A, B, C, D);
+
+// Missing arguments are passed as none, so `Option` parameters can be left out.
+macro_rules! host_function {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg: FromValue),*> HostFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R,
+            R: IntoResult,
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn call(&self, name: &str, args: Vec<Value>) -> IResult {
+                let arity = <[&str]>::len(&[$(stringify!($arg)),*]);
+                if args.len() > arity {
+                    return Err(NekoError::TypeError(format!(
+                        "{} expects at most {} arguments, got {}",
+                        name,
+                        arity,
+                        args.len()
+                    )));
+                }
+                let mut args = args.into_iter();
+                let mut index = 0;
+                $(
+                    index += 1;
+                    let $arg = <$arg>::from_value(args.next().unwrap_or(Value::None))
+                        .map_err(|err| match err {
+                            NekoError::TypeError(message) => NekoError::TypeError(format!(
+                                "{} argument {}: {}",
+                                name, index, message
+                            )),
+                            other => other,
+                        })?;
+                )*
+                self($($arg),*).into_result()
+            }
+        }
+    };
+}
+
+host_function!();
+host_function!(A);
+host_function!(A, B);
+host_function!(A, B, C);
+host_function!(A, B, C, D);
+host_function!(A, B, C, D, E);
+host_function!(A, B, C, D, E, G);
+
+#[test]
+fn should_round_trip_rust_values() {
+    let mut scores = HashMap::new();
+    scores.insert(String::from("ada"), vec![(1u8, true)]);
+    let value = scores.clone().into_value();
+    assert_eq!(
+        HashMap::<String, Vec<(u8, bool)>>::from_value(value).unwrap(),
+        scores
+    );
+    assert_eq!(Option::<String>::from_value(Value::None).unwrap(), None);
+    assert_eq!(i32::from_value(Value::Number(-4.0)).unwrap(), -4);
+    assert!(u8::from_value(Value::Number(256.0)).is_err());
+    assert!(i64::from_value(Value::Number(1.5)).is_err());
+    assert!(u64::from_value(Value::Number(18446744073709551616.0)).is_err());
+    assert!(i64::from_value(Value::Number(9223372036854775808.0)).is_err());
+    assert_eq!(
+        i64::from_value(Value::Number(-9223372036854775808.0)).unwrap(),
+        i64::MIN
+    );
+    assert!(u64::from_value(Value::Number(f64::INFINITY)).is_err());
+    assert!(u32::from_value(Value::Number(f64::NAN)).is_err());
+    assert!(String::from_value(Value::Number(1.0)).is_err());
+}
+
+#[test]
+fn should_call_typed_host_functions() {
+    use crate::interpreter::Interpreter;
+
+    let mut interpreter = Interpreter::new();
+    interpreter.register_typed_fn("repeat", |text: String, times: Option<usize>| {
+        text.repeat(times.unwrap_or(2))
+    });
+    interpreter.register_typed_fn("ratio", |a: f64, b: f64| {
+        if b == 0.0 {
+            Err(NekoError::TypeError(String::from("Division by zero")))
+        } else {
+            Ok(a / b)
+        }
+    });
+
+    assert_eq!(
+        interpreter
+            .interpret("repeat('ab') + repeat('c', 3);")
+            .unwrap(),
+        Value::String(String::from("ababccc"))
+    );
+    assert_eq!(
+        interpreter.interpret("ratio(3, 4);").unwrap(),
+        Value::Number(0.75)
+    );
+    assert_eq!(
+        interpreter.interpret("repeat(1);"),
+        Err(NekoError::TypeError(String::from(
+            "repeat argument 1: Expected string, got number"
+        )))
+    );
+    assert!(interpreter.interpret("repeat('a', 1.5);").is_err());
+    assert!(interpreter.interpret("ratio(1, 0);").is_err());
+    assert!(interpreter.interpret("ratio(1, 2, 3);").is_err());
+}
@@ -13,7 +13,7 @@ use std::{
     cell::RefCell,
 };
 
-use crate::{interpreter::Interpreter, interpreter_option::InterpreterOptions};
+use neko::{Interpreter, InterpreterOptions, Policy};
 
 pub struct OutputHint {
     pub display: String,
@@ -77,7 +77,7 @@ impl Hinter for EditorHelper {
             match self
                 .interpreter
                 .borrow_mut()
-                .interpret_with_option(line, &InterpreterOptions::all())
+                .interpret_with_option(line, &InterpreterOptions::with_policy(Policy::inert()))
             {
                 Ok(value) => {
                     let hint = self.hinter.hint(line, pos, ctx).unwrap_or_default();
@@ -102,7 +102,7 @@ impl Highlighter for EditorHelper {
         default: bool,
     ) -> Cow<'b, str> {
         if default {
-            Owned(format!("{}{}", prompt, Colour::White.suffix().to_string()))
+            Owned(format!("{}{}", prompt, Colour::White.suffix()))
         } else {
             Borrowed(prompt)
         }
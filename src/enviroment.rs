@@ -1,32 +1,80 @@
 use crate::{
-    ast::{FunctionDecleration, Lambda},
-    misc::NekoError,
+    ast::{EnumVariant, FunctionDecleration, Lambda},
+    generator::Generator,
+    interpreter::{IResult, Interpreter},
 };
+use regex::Regex;
 use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
 
 pub type Env = Rc<RefCell<Enviroment>>;
 
-#[derive(Debug, Clone, PartialEq)]
+/// A native function. Being a closure it can capture host state, and it can
+/// call back into Neko through the interpreter it is given.
+pub type NativeFunction = Rc<dyn Fn(&mut Interpreter, Vec<Value>) -> IResult>;
+
+#[derive(Clone)]
 pub enum FunctionType {
     Function(FunctionDecleration),
     Lambda(Lambda),
+    Constructor(String, EnumVariant),
     BuiltIn {
         name: String,
-        function: fn(args: Vec<Value>) -> Result<Value, NekoError>,
+        function: NativeFunction,
     },
 }
 
+impl FunctionType {
+    pub fn built_in<F>(name: &str, function: F) -> Self
+    where
+        F: Fn(&mut Interpreter, Vec<Value>) -> IResult + 'static,
+    {
+        FunctionType::BuiltIn {
+            name: String::from(name),
+            function: Rc::new(function),
+        }
+    }
+}
+
+impl fmt::Debug for FunctionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FunctionType::Function(function) => f.debug_tuple("Function").field(function).finish(),
+            FunctionType::Lambda(lambda) => f.debug_tuple("Lambda").field(lambda).finish(),
+            FunctionType::Constructor(enum_name, variant) => f
+                .debug_tuple("Constructor")
+                .field(enum_name)
+                .field(variant)
+                .finish(),
+            FunctionType::BuiltIn { name, .. } => f
+                .debug_struct("BuiltIn")
+                .field("name", name)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub enum_name: String,
+    pub name: String,
+    pub fields: Vec<(String, Value)>,
+}
+
+#[derive(Clone)]
 pub enum Value {
     Number(f64),
     Boolean(bool),
     Object(Rc<RefCell<HashMap<String, Box<Value>>>>),
+    List(Rc<RefCell<Vec<Value>>>),
+    Iterator(Rc<RefCell<Generator>>),
+    Regex(Rc<Regex>),
     Function(FunctionType, Env),
+    Variant(Rc<Variant>),
     String(String),
     None,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(PartialEq)]
 pub struct Enviroment {
     values: HashMap<String, Value>,
     pub enclosing_enviroment: Option<Env>,
@@ -66,10 +114,32 @@ impl Enviroment {
     pub fn define(&mut self, name: &str, value: Value) {
         self.values.insert(name.to_string(), value);
     }
+
+    /// Whether `name` is defined in this enviroment, not an enclosing one.
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+}
+
+// Functions hold on to the enviroment they are defined in, so printing values
+// would recurse forever, only the names are shown.
+impl fmt::Debug for Enviroment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Enviroment")
+            .field("values", &self.values.keys().collect::<Vec<_>>())
+            .field("enclosing_enviroment", &self.enclosing_enviroment)
+            .finish()
+    }
 }
 
 impl Value {
     pub fn stringify(&self) -> String {
+        self.stringify_in(&mut vec![])
+    }
+
+    /// `seen` holds the lists and objects being stringified, one containing
+    /// itself is shown as `[...]` or `{...}` instead of recursing forever.
+    fn stringify_in(&self, seen: &mut Vec<*const ()>) -> String {
         match self {
             Value::Number(num) => num.to_string(),
             Value::Boolean(boolean) => boolean.to_string(),
@@ -77,23 +147,174 @@ impl Value {
             Value::Function(function_type, _) => match function_type {
                 FunctionType::Function(function) => format!("[Function: {}]", function.name),
                 FunctionType::Lambda(_) => String::from("[Function: (lambda)]"),
-                FunctionType::BuiltIn { name, .. } => format!("[Built-In Function: {}]", name),
+                FunctionType::Constructor(enum_name, variant) => {
+                    format!("[Constructor: {}.{}]", enum_name, variant.name)
+                }
+                FunctionType::BuiltIn { name, .. } => {
+                    format!("[Built-In Function: {}]", name)
+                }
             },
+            Value::List(list) => {
+                let pointer = Rc::as_ptr(list) as *const ();
+                if seen.contains(&pointer) {
+                    return String::from("[...]");
+                }
+                seen.push(pointer);
+                let items = list
+                    .borrow()
+                    .iter()
+                    .map(|value| value.stringify_in(seen))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                seen.pop();
+                format!("[{}]", items)
+            }
+            Value::Iterator(_) => String::from("[Iterator]"),
+            Value::Regex(regex) => format!("/{}/", regex.as_str()),
+            Value::Variant(variant) => {
+                if variant.fields.is_empty() {
+                    format!("{}.{}", variant.enum_name, variant.name)
+                } else {
+                    format!(
+                        "{}.{}({})",
+                        variant.enum_name,
+                        variant.name,
+                        variant
+                            .fields
+                            .iter()
+                            .map(|(_, value)| value.stringify_in(seen))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )
+                }
+            }
             Value::Object(obj) => {
+                let pointer = Rc::as_ptr(obj) as *const ();
+                if seen.contains(&pointer) {
+                    return String::from("{...}");
+                }
+                seen.push(pointer);
                 let mut result = String::from("{");
                 result.push_str(
                     &obj.borrow()
                         .iter()
-                        .map(|(key, value)| format!(" {}: {}", key, &value.to_string()))
+                        .map(|(key, value)| format!(" {}: {}", key, value.stringify_in(seen)))
                         .collect::<Vec<String>>()
                         .join(", "),
                 );
                 result.push('}');
+                seen.pop();
                 result
             }
             Value::None => String::from("none"),
         }
     }
+
+    /// `seen` holds the pairs of lists and objects being compared, meeting a
+    /// pair again means both are cyclic in the same way, which counts as equal.
+    fn equals(&self, other: &Value, seen: &mut Vec<(*const (), *const ())>) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Object(a), Value::Object(b)) => {
+                let pair = (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ());
+                if Rc::ptr_eq(a, b) || seen.contains(&pair) {
+                    return true;
+                }
+                seen.push(pair);
+                let (a, b) = (a.borrow(), b.borrow());
+                let equal = a.len() == b.len()
+                    && a.iter().all(|(key, value)| {
+                        b.get(key).is_some_and(|other| value.equals(other, seen))
+                    });
+                seen.pop();
+                equal
+            }
+            (Value::List(a), Value::List(b)) => {
+                let pair = (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ());
+                if Rc::ptr_eq(a, b) || seen.contains(&pair) {
+                    return true;
+                }
+                seen.push(pair);
+                let (a, b) = (a.borrow(), b.borrow());
+                let equal =
+                    a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.equals(b, seen));
+                seen.pop();
+                equal
+            }
+            (Value::Iterator(a), Value::Iterator(b)) => Rc::ptr_eq(a, b),
+            (Value::Regex(a), Value::Regex(b)) => a.as_str() == b.as_str(),
+            // Closures are compared by identity, comparing their enviroments
+            // structurally would recurse into the function itself.
+            (Value::Function(a, a_env), Value::Function(b, b_env)) => {
+                a == b && Rc::ptr_eq(a_env, b_env)
+            }
+            (Value::Variant(a), Value::Variant(b)) => {
+                Rc::ptr_eq(a, b)
+                    || a.enum_name == b.enum_name
+                        && a.name == b.name
+                        && a.fields.len() == b.fields.len()
+                        && a.fields
+                            .iter()
+                            .zip(b.fields.iter())
+                            .all(|((a_name, a), (b_name, b))| a_name == b_name && a.equals(b, seen))
+            }
+            (Value::None, Value::None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq for FunctionType {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (FunctionType::Function(a), FunctionType::Function(b)) => a == b,
+            (FunctionType::Lambda(a), FunctionType::Lambda(b)) => a == b,
+            (FunctionType::Constructor(a, a_variant), FunctionType::Constructor(b, b_variant)) => {
+                a == b && a_variant == b_variant
+            }
+            (
+                FunctionType::BuiltIn {
+                    name: a,
+                    function: a_function,
+                },
+                FunctionType::BuiltIn {
+                    name: b,
+                    function: b_function,
+                },
+            ) => a == b && Rc::ptr_eq(a_function, b_function),
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.equals(other, &mut vec![])
+    }
+}
+
+// Lists and objects are shown like `Display` does, which stops at cycles.
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(num) => f.debug_tuple("Number").field(num).finish(),
+            Value::Boolean(boolean) => f.debug_tuple("Boolean").field(boolean).finish(),
+            Value::Object(_) => write!(f, "Object({})", self),
+            Value::List(_) => write!(f, "List({})", self),
+            Value::Iterator(generator) => f.debug_tuple("Iterator").field(generator).finish(),
+            Value::Regex(regex) => f.debug_tuple("Regex").field(regex).finish(),
+            Value::Function(function, env) => f
+                .debug_tuple("Function")
+                .field(function)
+                .field(env)
+                .finish(),
+            Value::Variant(variant) => f.debug_tuple("Variant").field(variant).finish(),
+            Value::String(string) => f.debug_tuple("String").field(string).finish(),
+            Value::None => f.write_str("None"),
+        }
+    }
 }
 
 impl fmt::Display for Value {
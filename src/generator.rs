@@ -0,0 +1,74 @@
+use crate::{
+    ast::Node,
+    enviroment::{Env, Value},
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// A source of values consumed one at a time by `for` loops and `list`.
+#[derive(Debug)]
+pub enum Iteration {
+    List(Rc<RefCell<Vec<Value>>>, usize),
+    Generator(Rc<RefCell<Generator>>),
+    /// An object following the iterator protocol, holding its `next` function.
+    Protocol(Value),
+}
+
+/// A suspended point of execution inside a generator function.
+#[derive(Debug)]
+pub enum Frame {
+    Block {
+        nodes: Vec<Node>,
+        index: usize,
+        env: Env,
+    },
+    Loop {
+        variable: String,
+        iteration: Iteration,
+        block: Vec<Node>,
+        env: Env,
+    },
+}
+
+#[derive(Debug)]
+pub enum Generator {
+    /// Values are computed from `start` and the index rather than added up,
+    /// so a fractional step does not accumulate rounding errors.
+    Range {
+        start: f64,
+        index: u64,
+        end: f64,
+        step: f64,
+    },
+    Function {
+        frames: Vec<Frame>,
+        running: bool,
+    },
+    /// Lines read lazily from the interpreter's input source.
+    Lines,
+}
+
+impl Generator {
+    pub fn range(start: f64, end: f64, step: f64) -> Self {
+        Generator::Range {
+            start,
+            index: 0,
+            end,
+            step,
+        }
+    }
+
+    pub fn function(block: &Node, env: Env) -> Self {
+        let nodes = match block {
+            Node::Block(nodes) => nodes.clone(),
+            node => vec![node.clone()],
+        };
+        Generator::Function {
+            frames: vec![Frame::Block {
+                nodes,
+                index: 0,
+                env,
+            }],
+            running: false,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    fmt::Debug,
+    io::{self, BufRead, Read, Write},
+};
+
+/// Where `input`, `read_line`, `read_all` and `lines` read from.
+pub trait InputSource: Debug {
+    /// Reads one line without its line ending, `None` once the input is exhausted.
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>>;
+
+    /// Reads the rest of the input. Sources that can should return it unchanged,
+    /// the default joins the remaining lines and loses the final line ending.
+    fn read_all(&mut self) -> io::Result<String> {
+        let mut lines = vec![];
+        while let Some(line) = self.read_line("")? {
+            lines.push(line);
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+fn trim_line_ending(mut line: String) -> String {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    line
+}
+
+#[derive(Debug)]
+pub struct StdinInput;
+
+impl InputSource for StdinInput {
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        if !prompt.is_empty() {
+            print!("{}", prompt);
+            io::stdout().flush()?;
+        }
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(trim_line_ending(line))),
+        }
+    }
+
+    fn read_all(&mut self) -> io::Result<String> {
+        let mut content = String::new();
+        io::stdin().lock().read_to_string(&mut content)?;
+        Ok(content)
+    }
+}
+
+/// Input backed by a string, for tests and embedders feeding scripts data.
+#[derive(Debug)]
+pub struct StringInput {
+    text: String,
+    position: usize,
+}
+
+impl StringInput {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: String::from(text),
+            position: 0,
+        }
+    }
+}
+
+impl InputSource for StringInput {
+    fn read_line(&mut self, _prompt: &str) -> io::Result<Option<String>> {
+        let rest = &self.text[self.position..];
+        if rest.is_empty() {
+            return Ok(None);
+        }
+        let end = rest.find('\n').map_or(rest.len(), |index| index + 1);
+        self.position += end;
+        Ok(Some(trim_line_ending(String::from(&rest[..end]))))
+    }
+
+    /// Returns the rest of the text unchanged, like `StdinInput` does.
+    fn read_all(&mut self) -> io::Result<String> {
+        let rest = String::from(&self.text[self.position..]);
+        self.position = self.text.len();
+        Ok(rest)
+    }
+}
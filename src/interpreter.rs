@@ -1,12 +1,51 @@
 use crate::{
-    ast::*, enviroment::*, interpreter_option::InterpreterOptions, misc::NekoError, parser::Parser,
-    semantic_analyzer::SemanticAnalyzer, token::*,
+    ast::*,
+    conversion::HostFunction,
+    enviroment::*,
+    generator::{Frame, Generator, Iteration},
+    input::{InputSource, StdinInput},
+    interpreter_option::InterpreterOptions,
+    misc::{NekoError, NekoWarning},
+    parser::Parser,
+    semantic_analyzer::SemanticAnalyzer,
+    stdlib::{self, convert::type_of, random::Random, time::Clock},
+    symbol::Symbol,
+    token::*,
 };
 use ansi_term::Colour;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    mem,
+    rc::{Rc, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 pub type IResult = Result<Value, NekoError>;
 
+/// Approximate bytes a value takes in a list, object or enviroment.
+pub const VALUE_SIZE: usize = mem::size_of::<Value>();
+
+/// Identifies objects, lists and native functions by the allocation they share
+/// between copies, other values have no identity.
+fn identity(value: &Value) -> Option<*const ()> {
+    match value {
+        Value::Object(obj) => Some(Rc::as_ptr(obj) as *const ()),
+        Value::List(list) => Some(Rc::as_ptr(list) as *const ()),
+        Value::Function(FunctionType::BuiltIn { function, .. }, _) => {
+            Some(Rc::as_ptr(function) as *const ())
+        }
+        _ => None,
+    }
+}
+
+/// A native method, bound to the value it is read from such as `list.push`.
+pub type Method = fn(interpreter: &mut Interpreter, receiver: Value, args: Vec<Value>) -> IResult;
+
 fn convert_f64_usize(x: f64) -> Result<usize, String> {
     let result = x as usize;
     if (result as f64 - x).abs() > 0.0 {
@@ -16,95 +55,591 @@ fn convert_f64_usize(x: f64) -> Result<usize, String> {
     }
 }
 
-fn to_bool(val: &Value) -> bool {
+pub fn to_bool(val: &Value) -> bool {
     match val {
         Value::Number(num) => num.ne(&0.0),
         Value::String(string) => !string.is_empty(),
         Value::Boolean(boolean) => *boolean,
         Value::Object(obj) => !obj.borrow().is_empty(),
+        Value::List(list) => !list.borrow().is_empty(),
+        Value::Iterator(_) => true,
+        Value::Regex(_) => true,
         Value::Function(..) => true,
+        Value::Variant(_) => true,
         Value::None => false,
     }
 }
 
 pub fn colored_output(val: &Value) -> String {
+    output(val, true)
+}
+
+/// The output shown by the REPL and `print`, `repr` uses it without colours.
+pub fn output(val: &Value, colored: bool) -> String {
+    output_in(val, colored, &mut vec![])
+}
+
+/// `seen` holds the lists and objects being shown, like in `Value::stringify`.
+fn output_in(val: &Value, colored: bool, seen: &mut Vec<*const ()>) -> String {
+    let paint = |colour: Colour, text: String| {
+        if colored {
+            colour.paint(text).to_string()
+        } else {
+            text
+        }
+    };
     match val {
-        Value::Number(num) => format!("{}", Colour::Yellow.paint(num.to_string())),
-        Value::Boolean(boolean) => format!("{}", Colour::Yellow.paint(boolean.to_string())),
-        Value::String(string) => format!("{}", Colour::Green.paint(format!("{:?}", string))),
-        Value::Function(function_type, _) => format!(
-            "{}",
+        Value::Number(num) => paint(Colour::Yellow, num.to_string()),
+        Value::Boolean(boolean) => paint(Colour::Yellow, boolean.to_string()),
+        Value::String(string) => paint(Colour::Green, format!("{:?}", string)),
+        Value::Function(function_type, _) => paint(
+            Colour::Green,
             match function_type {
-                FunctionType::Function(function) =>
-                    Colour::Green.paint(format!("[Function: {}]", function.name)),
-                FunctionType::Lambda(_) => Colour::Green.paint("[Function: (lambda)]"),
-                FunctionType::BuiltIn { name, .. } =>
-                    Colour::Green.paint(format!("[Built-In Function: {}]", name)),
-            }
+                FunctionType::Function(function) => format!("[Function: {}]", function.name),
+                FunctionType::Lambda(_) => String::from("[Function: (lambda)]"),
+                FunctionType::Constructor(enum_name, variant) => {
+                    format!("[Constructor: {}.{}]", enum_name, variant.name)
+                }
+                FunctionType::BuiltIn { name, .. } => {
+                    format!("[Built-In Function: {}]", name)
+                }
+            },
         ),
         Value::Object(obj) => {
+            let pointer = Rc::as_ptr(obj) as *const ();
+            if seen.contains(&pointer) {
+                return String::from("{...}");
+            }
+            seen.push(pointer);
             let mut result = String::from("{");
             result.push_str(
                 &obj.borrow()
                     .iter()
-                    .map(|(key, value)| format!(" {}: {}", key, colored_output(&value)))
+                    .map(|(key, value)| format!(" {}: {}", key, output_in(value, colored, seen)))
                     .collect::<Vec<String>>()
                     .join(", "),
             );
             result.push('}');
+            seen.pop();
+            result
+        }
+        Value::List(list) => {
+            let pointer = Rc::as_ptr(list) as *const ();
+            if seen.contains(&pointer) {
+                return String::from("[...]");
+            }
+            seen.push(pointer);
+            let items = list
+                .borrow()
+                .iter()
+                .map(|value| output_in(value, colored, seen))
+                .collect::<Vec<String>>()
+                .join(", ");
+            seen.pop();
+            format!("[{}]", items)
+        }
+        Value::Iterator(_) => paint(Colour::Green, String::from("[Iterator]")),
+        Value::Regex(regex) => paint(Colour::Red, format!("/{}/", regex.as_str())),
+        Value::Variant(variant) => {
+            let mut result = format!("{}.{}", variant.enum_name, variant.name);
+            if !variant.fields.is_empty() {
+                result.push('(');
+                result.push_str(
+                    &variant
+                        .fields
+                        .iter()
+                        .map(|(_, value)| output_in(value, colored, seen))
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                );
+                result.push(')');
+            }
             result
         }
-        Value::None => Colour::RGB(128, 127, 113).paint("none").to_string(),
+        Value::None => paint(Colour::RGB(128, 127, 113), String::from("none")),
+    }
+}
+
+fn list_index(index: &Value) -> Result<usize, NekoError> {
+    match index {
+        Value::Number(num) => convert_f64_usize(*num).map_err(|_| {
+            NekoError::TypeError(format!(
+                "List indices must be non-negative integers, got {}",
+                num
+            ))
+        }),
+        other => Err(NekoError::TypeError(format!(
+            "List indices must be numbers, got {}",
+            other
+        ))),
+    }
+}
+
+fn iterator_result(value: Option<Value>) -> Value {
+    let mut result: HashMap<String, Box<Value>> = HashMap::new();
+    result.insert(
+        String::from("done"),
+        Box::new(Value::Boolean(value.is_none())),
+    );
+    result.insert(
+        String::from("value"),
+        Box::new(value.unwrap_or(Value::None)),
+    );
+    Value::Object(Rc::new(RefCell::new(result)))
+}
+
+fn iterator_method(name: &str) -> Option<Method> {
+    match name {
+        "next" => Some(|interpreter, receiver, _| match receiver {
+            Value::Iterator(generator) => {
+                let value = interpreter.resume_generator(&generator)?;
+                Ok(interpreter.created(iterator_result(value)))
+            }
+            _ => unreachable!(),
+        }),
+        _ => None,
+    }
+}
+
+/// Cancels evaluations of the interpreter it was taken from, it can be sent to
+/// other threads. An interrupt raised while nothing is running is ignored.
+#[derive(Debug, Clone)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::Relaxed)
     }
 }
 
+/// Evaluates Neko source, keeping globals alive between `interpret` calls.
 #[derive(Debug)]
 pub struct Interpreter {
     env: Env,
     semantic_analyzer: SemanticAnalyzer,
     interpreter_options: InterpreterOptions,
+    /// Options restored by `interpret` before every evaluation.
+    default_options: InterpreterOptions,
+    input: Box<dyn InputSource>,
+    clock: Clock,
+    random: Random,
+    /// Steps left in the current evaluation, `None` when unlimited.
+    fuel: Option<u64>,
+    depth: usize,
+    interrupt: Arc<AtomicBool>,
+    /// Approximate bytes allocated by the current evaluation.
+    allocated: usize,
+    /// Objects and lists scripts may read but not change, such as `process.env`
+    /// and enums.
+    read_only: HashMap<*const (), Weak<dyn Any>>,
+    /// Built-in module values with the module they belong to, hidden from
+    /// evaluations whose policy does not allow the module.
+    modules: Vec<(&'static str, Value)>,
+    /// Objects, lists and enviroments created by the current evaluation, the
+    /// only ones it may change when the policy disallows mutation.
+    // Pointers are only compared. Values that existed before the evaluation
+    // were allocated before it, so none of them can have an address listed.
+    created: HashSet<*const ()>,
+    /// Variables the current evaluation declared in enviroments it did not
+    /// create, which it may assign to as well.
+    declared: HashMap<*const (), HashSet<String>>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
+        Self::with_options(InterpreterOptions::new())
+    }
+
+    pub fn with_options(options: InterpreterOptions) -> Self {
         let mut interpreter = Self {
             env: Rc::new(RefCell::new(Enviroment::new(None))),
             semantic_analyzer: SemanticAnalyzer::new(),
-            interpreter_options: InterpreterOptions::new(),
+            clock: Clock::new(options.fake_clock),
+            random: Random::new(options.seed),
+            interpreter_options: options.clone(),
+            fuel: options.policy.fuel,
+            default_options: options,
+            input: Box::new(StdinInput),
+            depth: 0,
+            interrupt: Arc::new(AtomicBool::new(false)),
+            allocated: 0,
+            read_only: HashMap::new(),
+            modules: vec![],
+            created: HashSet::new(),
+            declared: HashMap::new(),
         };
         interpreter.set_up_env();
         interpreter
     }
 
+    pub fn options(&self) -> &InterpreterOptions {
+        &self.interpreter_options
+    }
+
+    pub fn input(&mut self) -> &mut dyn InputSource {
+        self.input.as_mut()
+    }
+
+    pub fn clock(&mut self) -> &mut Clock {
+        &mut self.clock
+    }
+
+    pub fn random(&mut self) -> &mut Random {
+        &mut self.random
+    }
+
+    pub fn set_input(&mut self, input: Box<dyn InputSource>) {
+        self.input = input;
+    }
+
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle(Arc::clone(&self.interrupt))
+    }
+
+    /// Fails with `NekoError::Cancelled` once the interrupt handle was used,
+    /// for native functions that block for a long time.
+    pub fn check_interrupt(&self) -> Result<(), NekoError> {
+        if self.interrupt.swap(false, Ordering::Relaxed) {
+            Err(NekoError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Defines a native function scripts can call as `name`.
+    pub fn register_fn<F>(&mut self, name: &str, function: F)
+    where
+        F: Fn(&mut Interpreter, Vec<Value>) -> IResult + 'static,
+    {
+        let globals = self.globals();
+        let function = Value::Function(FunctionType::built_in(name, function), Rc::clone(&globals));
+        globals.borrow_mut().define(name, function);
+        self.semantic_analyzer
+            .scope
+            .borrow_mut()
+            .insert(name, Symbol::BuiltInSymbol(String::from(name)));
+    }
+
+    /// Defines a function taking and returning Rust types converted with
+    /// `FromValue` and `IntoValue`, mismatched arguments raise a TypeError.
+    pub fn register_typed_fn<F, Args>(&mut self, name: &str, function: F)
+    where
+        F: HostFunction<Args> + 'static,
+        Args: 'static,
+    {
+        let owned = String::from(name);
+        self.register_fn(name, move |_, args| function.call(&owned, args));
+    }
+
+    /// Defines the global `name`, replacing any previous value.
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.globals().borrow_mut().define(name, value);
+        self.semantic_analyzer
+            .scope
+            .borrow_mut()
+            .insert(name, Symbol::VarSymbol);
+    }
+
+    /// Returns the global `name`, `None` if it is not defined.
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.globals().borrow().look_up(name, true)
+    }
+
+    /// Calls a Neko function, such as one returned by `get_global`, from Rust.
+    pub fn call(&mut self, function: &Value, args: Vec<Value>) -> IResult {
+        let options =
+            std::mem::replace(&mut self.interpreter_options, self.default_options.clone());
+        self.reset_limits();
+        let result = self.call_function(function.clone(), args);
+        self.interpreter_options = options;
+        result
+    }
+
+    /// Fails unless scripts may change `value` in place, called before
+    /// setting properties or items and by mutating methods.
+    pub fn check_mutable(&self, value: &Value) -> Result<(), NekoError> {
+        let pointer = match value {
+            Value::Object(_) | Value::List(_) => identity(value),
+            _ => None,
+        };
+        match pointer {
+            Some(pointer) if self.read_only.contains_key(&pointer) => Err(NekoError::TypeError(
+                format!("Cannot modify a read-only {}", type_of(value)),
+            )),
+            Some(pointer)
+                if !self.interpreter_options.policy.mutation && !self.created.contains(&pointer) =>
+            {
+                Err(NekoError::PermissionError(format!(
+                    "Cannot modify a {} created before this evaluation, mutation is not allowed by the policy",
+                    type_of(value)
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails unless the current evaluation's policy allows `module`, called
+    /// by the module's native functions.
+    pub fn check_module(&self, module: &str) -> Result<(), NekoError> {
+        if self.interpreter_options.policy.allows_module(module) {
+            Ok(())
+        } else {
+            Err(NekoError::PermissionError(format!(
+                "Module '{}' is not allowed by the policy",
+                module
+            )))
+        }
+    }
+
+    /// Fails if `value` belongs to a module the policy does not allow,
+    /// however the script got hold of it.
+    fn check_module_value(&self, value: &Value) -> Result<(), NekoError> {
+        let pointer = match identity(value) {
+            Some(pointer) => pointer,
+            None => return Ok(()),
+        };
+        match self
+            .modules
+            .iter()
+            .find(|(_, module_value)| identity(module_value) == Some(pointer))
+        {
+            Some((module, _)) => self.check_module(module),
+            None => Ok(()),
+        }
+    }
+
+    // Pointers are only compared, never dereferenced. The weak reference keeps
+    // the allocation, and so the address, from being reused while it is listed,
+    // entries of dropped values are removed as new ones are added.
+    fn freeze(&mut self, value: &Value) {
+        let weak: Weak<dyn Any> = match value {
+            Value::Object(obj) => Rc::downgrade(obj) as Weak<_>,
+            Value::List(list) => Rc::downgrade(list) as Weak<_>,
+            _ => return,
+        };
+        self.read_only.retain(|_, weak| weak.strong_count() > 0);
+        self.read_only.insert(weak.as_ptr() as *const (), weak);
+    }
+
+    /// Records `value` as created by the current evaluation, so it stays
+    /// mutable when the policy disallows changing what existed before. Native
+    /// functions call this on the objects and lists they return, others count
+    /// as existing ones.
+    pub fn created(&mut self, value: Value) -> Value {
+        if !self.interpreter_options.policy.mutation {
+            if let Value::Object(_) | Value::List(_) = value {
+                self.created.extend(identity(&value));
+            }
+        }
+        value
+    }
+
+    /// A new enviroment enclosed by `enclosing`, recorded like `created` values.
+    fn scope(&mut self, enclosing: Env) -> Env {
+        let env = Rc::new(RefCell::new(Enviroment::new(Some(enclosing))));
+        if !self.interpreter_options.policy.mutation {
+            self.created.insert(Rc::as_ptr(&env) as *const ());
+        }
+        env
+    }
+
+    /// Fails if assigning `name` would change a variable that existed when
+    /// the evaluation started, while the policy disallows mutation.
+    fn check_assignment(&self, name: &str) -> Result<(), NekoError> {
+        if self.interpreter_options.policy.mutation {
+            return Ok(());
+        }
+        let mut env = Rc::clone(&self.env);
+        loop {
+            if env.borrow().contains(name) {
+                let pointer = Rc::as_ptr(&env) as *const ();
+                let allowed = self.created.contains(&pointer)
+                    || self
+                        .declared
+                        .get(&pointer)
+                        .is_some_and(|names| names.contains(name));
+                return if allowed {
+                    Ok(())
+                } else {
+                    Err(NekoError::PermissionError(format!(
+                        "Cannot modify '{}', mutation is not allowed by the policy",
+                        name
+                    )))
+                };
+            }
+            let enclosing = env.borrow().enclosing_enviroment.clone();
+            match enclosing {
+                Some(enclosing) => env = enclosing,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Defines `name` in the current enviroment, recording it so the
+    /// evaluation may assign to it even when mutation is not allowed. The
+    /// semantic analyzer already rejects declaring a name twice in a scope.
+    fn declare(&mut self, name: &str, value: Value) {
+        if !self.interpreter_options.policy.mutation {
+            let pointer = Rc::as_ptr(&self.env) as *const ();
+            if !self.created.contains(&pointer) {
+                self.declared
+                    .entry(pointer)
+                    .or_default()
+                    .insert(String::from(name));
+            }
+        }
+        self.env.borrow_mut().define(name, value);
+    }
+
+    // Every evaluation started by the host gets a fresh budget, native
+    // functions calling back into Neko keep spending the current one.
+    fn reset_limits(&mut self) {
+        if self.depth == 0 {
+            self.fuel = self.interpreter_options.policy.fuel;
+            self.allocated = 0;
+            self.interrupt.store(false, Ordering::Relaxed);
+            self.created.clear();
+            self.declared.clear();
+        }
+    }
+
+    /// Counts `bytes` against the allocation budget, called before allocating
+    /// them so oversized strings and lists fail instead of exhausting the host.
+    /// Nothing is credited back when values are dropped.
+    pub fn allocate(&mut self, bytes: usize) -> Result<(), NekoError> {
+        self.allocated = self.allocated.saturating_add(bytes);
+        match self.interpreter_options.policy.max_allocation {
+            Some(max_allocation) if self.allocated > max_allocation => {
+                Err(NekoError::ResourceError(format!(
+                    "Allocation budget of {} bytes exceeded",
+                    max_allocation
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Bytes the current evaluation may still allocate, `None` when unlimited.
+    /// For native functions that can only tell the size of a result while
+    /// building it.
+    pub fn allocation_left(&self) -> Option<usize> {
+        self.interpreter_options
+            .policy
+            .max_allocation
+            .map(|max_allocation| max_allocation.saturating_sub(self.allocated))
+    }
+
+    fn step(&mut self) -> Result<(), NekoError> {
+        self.check_interrupt()?;
+        match self.fuel {
+            Some(0) => Err(NekoError::ResourceError(format!(
+                "Step budget of {} exceeded",
+                self.interpreter_options.policy.fuel.unwrap_or(0)
+            ))),
+            Some(fuel) => {
+                self.fuel = Some(fuel - 1);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Native functions can run while a Neko function's enviroment is the
+    // current one, so the globals are found by walking up to the outermost.
+    fn globals(&self) -> Env {
+        let mut env = Rc::clone(&self.env);
+        loop {
+            let enclosing = env.borrow().enclosing_enviroment.clone();
+            match enclosing {
+                Some(enclosing) => env = enclosing,
+                None => return env,
+            }
+        }
+    }
+
     fn set_up_env(&mut self) {
         let built_in = vec![
             Value::Function(
-                FunctionType::BuiltIn {
-                    name: String::from("print"),
-                    function: |args| {
-                        println!(
-                            "{}",
-                            args.iter()
-                                .map(colored_output)
-                                .collect::<Vec<String>>()
-                                .join(" ")
-                        );
-                        Ok(Value::None)
-                    },
-                },
+                FunctionType::built_in("print", |interpreter, args| {
+                    let mut output = vec![];
+                    for arg in &args {
+                        output.push(match interpreter.str_metamethod(arg)? {
+                            Some(string) => string,
+                            None => colored_output(arg),
+                        })
+                    }
+                    println!("{}", output.join(" "));
+                    Ok(Value::None)
+                }),
+                Rc::clone(&self.env),
+            ),
+            Value::Function(
+                FunctionType::built_in("error", |interpreter, args| {
+                    if let Some(val) = args.first() {
+                        Err(NekoError::UnknownError(
+                            match interpreter.str_metamethod(val)? {
+                                Some(string) => string,
+                                None => val.stringify(),
+                            },
+                        ))
+                    } else {
+                        Err(NekoError::TypeError(String::from("Expect value got none.")))
+                    }
+                }),
                 Rc::clone(&self.env),
             ),
             Value::Function(
-                FunctionType::BuiltIn {
-                    name: String::from("error"),
-                    function: |args| {
-                        if let Some(val) = args.first() {
-                            Err(NekoError::UnknownError(val.stringify()))
-                        } else {
-                            Err(NekoError::TypeError(String::from("Expect value got none.")))
+                FunctionType::built_in("range", |_, args| {
+                    let mut numbers = vec![];
+                    for arg in &args {
+                        match arg {
+                            Value::Number(num) => numbers.push(*num),
+                            other => {
+                                return Err(NekoError::TypeError(format!(
+                                    "Expected Number for range, got {}",
+                                    other
+                                )))
+                            }
                         }
-                    },
-                },
+                    }
+                    let (start, end, step) = match numbers[..] {
+                        [end] => (0.0, end, 1.0),
+                        [start, end] => (start, end, 1.0),
+                        [start, end, step] => (start, end, step),
+                        _ => {
+                            return Err(NekoError::TypeError(format!(
+                                "range expects 1 to 3 arguments, got {}",
+                                numbers.len()
+                            )))
+                        }
+                    };
+                    if step == 0.0 {
+                        return Err(NekoError::TypeError(String::from("range step cannot be 0")));
+                    }
+                    Ok(Value::Iterator(Rc::new(RefCell::new(Generator::range(
+                        start, end, step,
+                    )))))
+                }),
+                Rc::clone(&self.env),
+            ),
+            Value::Function(
+                FunctionType::built_in("list", |interpreter, args| {
+                    let mut list = vec![];
+                    if let Some(iterable) = args.into_iter().next() {
+                        let mut iteration = interpreter.iterate(iterable)?;
+                        while let Some(value) = interpreter.next_item(&mut iteration)? {
+                            interpreter.allocate(VALUE_SIZE)?;
+                            list.push(value)
+                        }
+                    }
+                    Ok(interpreter.created(Value::List(Rc::new(RefCell::new(list)))))
+                }),
                 Rc::clone(&self.env),
             ),
         ];
@@ -112,11 +647,94 @@ impl Interpreter {
         for built in built_in {
             match built {
                 Value::Function(FunctionType::BuiltIn { ref name, .. }, _) => {
-                    self.env.borrow_mut().define(&name, built.clone())
+                    self.env.borrow_mut().define(name, built.clone())
                 }
                 _ => unreachable!(),
             }
         }
+
+        for (name, value) in stdlib::globals(&self.env, &self.default_options) {
+            if let Some(module) = stdlib::module_of(name) {
+                self.modules.push((module, value.clone()));
+                if let Value::Object(obj) = &value {
+                    for field in obj.borrow().values() {
+                        if let Value::Object(_) | Value::List(_) = **field {
+                            self.modules.push((module, *field.clone()));
+                        }
+                    }
+                }
+            }
+            self.env.borrow_mut().define(name, value)
+        }
+
+        if let Some(Value::Object(process)) = self.get_global("process") {
+            let variables = process.borrow().get("env").map(|env| *env.clone());
+            if let Some(variables) = variables {
+                self.freeze(&variables)
+            }
+        }
+    }
+
+    fn metamethod(&self, value: &Value, name: &str) -> Option<Value> {
+        match value {
+            Value::Object(obj) => match obj.borrow().get(name) {
+                Some(method) => match **method {
+                    Value::Function(..) | Value::Object(_) => Some(*method.clone()),
+                    _ => None,
+                },
+                None => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn str_metamethod(&mut self, value: &Value) -> Result<Option<String>, NekoError> {
+        match self.metamethod(value, "__str") {
+            Some(method) => match self.call_function(method, vec![value.clone()])? {
+                Value::String(string) => Ok(Some(string)),
+                other => Err(NekoError::TypeError(format!(
+                    "__str must return a String, got {}",
+                    other
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn binary_metamethod(
+        &mut self,
+        operator: &Token,
+        left: &Value,
+        right: &Value,
+    ) -> Result<Option<Value>, NekoError> {
+        let (name, swap, negate) = match operator {
+            Token::Operator(Operator::Plus) => ("__add", false, false),
+            Token::Operator(Operator::Minus) => ("__sub", false, false),
+            Token::Operator(Operator::Mul) => ("__mul", false, false),
+            Token::Operator(Operator::Div) => ("__div", false, false),
+            Token::Operator(Operator::Modulus) => ("__mod", false, false),
+            Token::Operator(Operator::Exponent) => ("__pow", false, false),
+            Token::Operator(Operator::DoubleEqual) => ("__eq", false, false),
+            Token::Operator(Operator::NotEqual) => ("__eq", false, true),
+            Token::Operator(Operator::LessThan) => ("__lt", false, false),
+            Token::Operator(Operator::GreaterThan) => ("__lt", true, false),
+            Token::Operator(Operator::LessThanOrEqual) => ("__le", false, false),
+            Token::Operator(Operator::GreaterThanOrEqual) => ("__le", true, false),
+            _ => return Ok(None),
+        };
+        let (left, right) = if swap { (right, left) } else { (left, right) };
+        let method = match self
+            .metamethod(left, name)
+            .or_else(|| self.metamethod(right, name))
+        {
+            Some(method) => method,
+            None => return Ok(None),
+        };
+        let result = self.call_function(method, vec![left.clone(), right.clone()])?;
+        Ok(Some(match name {
+            "__eq" | "__lt" | "__le" => Value::Boolean(to_bool(&result) != negate),
+            _ => result,
+        }))
     }
 
     fn number_operation(
@@ -156,10 +774,18 @@ impl Interpreter {
             self.visit_expression(&node.left)?,
             self.visit_expression(&node.right)?,
         );
+        if let (Value::Object(_), _) | (_, Value::Object(_)) = (&left, &right) {
+            if let Some(result) = self.binary_metamethod(&node.operator, &left, &right)? {
+                return Ok(result);
+            }
+        }
         match node.operator {
             Token::Operator(Operator::Plus) => match (left, right) {
                 (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a + b)),
-                (Value::String(a), Value::String(b)) => Ok(Value::String(format!("{}{}", a, b))),
+                (Value::String(a), Value::String(b)) => {
+                    self.allocate(a.len() + b.len())?;
+                    Ok(Value::String(format!("{}{}", a, b)))
+                }
                 (a, b) => Err(NekoError::TypeError(format!(
                     "Mismatched types for binary Add, got {:?} and {:?}",
                     a, b
@@ -170,13 +796,15 @@ impl Interpreter {
             }
             Token::Operator(Operator::Mul) => match (left, right) {
                 (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a * b)),
-                (Value::String(a), Value::Number(b)) | (Value::Number(b), Value::String(a)) => Ok(
-                    Value::String(a.repeat(convert_f64_usize(b).map_err(|_| {
-                        NekoError::TypeError(
-                        String::from("Can't multiply sequence by non-positive int of type float or negative int"),
-                    )
-                    })?)),
-                ),
+                (Value::String(a), Value::Number(b)) | (Value::Number(b), Value::String(a)) => {
+                    let count = convert_f64_usize(b).map_err(|_| {
+                        NekoError::TypeError(String::from(
+                            "Can't multiply sequence by non-positive int of type float or negative int",
+                        ))
+                    })?;
+                    self.allocate(a.len().saturating_mul(count))?;
+                    Ok(Value::String(a.repeat(count)))
+                }
                 (a, b) => Err(NekoError::TypeError(format!(
                     "Mismatched types for binary Mul, got {:?} and {:?}",
                     a, b
@@ -219,7 +847,10 @@ impl Interpreter {
                     Ok(right)
                 }
             }
-            _ => Err(NekoError::SyntaxError(format!("Expected Operator, got {}.", node))),
+            _ => Err(NekoError::SyntaxError(format!(
+                "Expected Operator, got {}.",
+                node
+            ))),
         }
     }
 
@@ -228,10 +859,13 @@ impl Interpreter {
             Token::Operator(Operator::Plus) => self.visit_expression(&node.expression),
             Token::Operator(Operator::Minus) => match self.visit_expression(&node.expression)? {
                 Value::Number(num) => Ok(Value::Number(-num)),
-                other => Err(NekoError::TypeError(format!(
-                    "Expected Number for Unary {:?}, got {:?}",
-                    node.operator, other
-                ))),
+                other => match self.metamethod(&other, "__neg") {
+                    Some(method) => self.call_function(method, vec![other]),
+                    None => Err(NekoError::TypeError(format!(
+                        "Expected Number for Unary {:?}, got {:?}",
+                        node.operator, other
+                    ))),
+                },
             },
             Token::Operator(Operator::Not) => {
                 let value = self.visit_expression(&node.expression)?;
@@ -256,7 +890,7 @@ impl Interpreter {
         let mut result = Value::None;
 
         for node in nodes {
-            match self.visit(&node)? {
+            match self.visit(node)? {
                 Value::None => (),
                 val => result = val,
             }
@@ -266,7 +900,7 @@ impl Interpreter {
     }
 
     fn visit_variable_decleration(&mut self, node: &VariabeDecleration) -> IResult {
-        if !self.interpreter_options.disable_decleration {
+        if self.interpreter_options.policy.declarations {
             let value = match &node.value {
                 Some(value_node) => match self.visit(value_node) {
                     Ok(val) => val,
@@ -280,16 +914,41 @@ impl Interpreter {
                 },
                 None => Value::None,
             };
-            self.env.borrow_mut().define(&node.identifier, value);
+            self.declare(&node.identifier, value);
         }
         Ok(Value::None)
     }
 
     fn visit_function_decleration(&mut self, node: &FunctionDecleration) -> IResult {
-        if !self.interpreter_options.disable_decleration {
+        if self.interpreter_options.policy.declarations {
             let function =
                 Value::Function(FunctionType::Function(node.clone()), Rc::clone(&self.env));
-            self.env.borrow_mut().define(&node.name, function);
+            self.declare(&node.name, function);
+        }
+        Ok(Value::None)
+    }
+
+    fn visit_enum_decleration(&mut self, node: &EnumDecleration) -> IResult {
+        if self.interpreter_options.policy.declarations {
+            let mut variants: HashMap<String, Box<Value>> = HashMap::new();
+            for variant in &node.variants {
+                let value = if variant.fields.is_empty() {
+                    Value::Variant(Rc::new(Variant {
+                        enum_name: node.name.clone(),
+                        name: variant.name.clone(),
+                        fields: vec![],
+                    }))
+                } else {
+                    Value::Function(
+                        FunctionType::Constructor(node.name.clone(), variant.clone()),
+                        Rc::clone(&self.env),
+                    )
+                };
+                variants.insert(variant.name.clone(), Box::new(value));
+            }
+            let value = Value::Object(Rc::new(RefCell::new(variants)));
+            self.freeze(&value);
+            self.declare(&node.name, value);
         }
         Ok(Value::None)
     }
@@ -304,7 +963,7 @@ impl Interpreter {
         let mut result = Value::None;
 
         for node in nodes {
-            match self.visit(&node)? {
+            match self.visit(node)? {
                 Value::None => (),
                 val => result = val,
             }
@@ -313,69 +972,183 @@ impl Interpreter {
         Ok(result)
     }
 
+    fn visit_with_env(&mut self, node: &Node, env: Env) -> IResult {
+        let previous = Rc::clone(&self.env);
+        self.env = env;
+        let result = self.visit(node);
+        self.env = previous;
+        result
+    }
+
     fn function_call(
         &mut self,
-        node: &FunctionCall,
         params: &[String],
         block: &Node,
         closure: Env,
+        arguments: Vec<Value>,
+        generator: bool,
     ) -> IResult {
-        self.env = Rc::new(RefCell::new(Enviroment::new(Some(closure))));
+        let env = self.scope(closure);
 
-        for (index, param) in params.iter().enumerate() {
-            let value = match node.arguments.get(index) {
-                Some(node) => self.visit(node)?,
-                None => Value::None,
-            };
-            self.env.borrow_mut().define(&param, value)
+        let mut arguments = arguments.into_iter();
+        for param in params {
+            let value = arguments.next().unwrap_or(Value::None);
+            env.borrow_mut().define(param, value)
         }
 
-        let result = self.visit(&block)?;
+        if generator {
+            Ok(Value::Iterator(Rc::new(RefCell::new(Generator::function(
+                block, env,
+            )))))
+        } else {
+            self.visit_with_env(block, env)
+        }
+    }
 
-        self.env = Rc::clone(
-            Rc::clone(&self.env)
-                .borrow()
-                .enclosing_enviroment
-                .as_ref()
-                .unwrap(),
-        );
+    // Calls, including `__call` dispatch and native callbacks, and generator
+    // resumptions all nest on the Rust stack, so each counts as a level.
+    fn nested<T>(
+        &mut self,
+        run: impl FnOnce(&mut Self) -> Result<T, NekoError>,
+    ) -> Result<T, NekoError> {
+        if let Some(max_depth) = self.interpreter_options.policy.max_depth {
+            if self.depth >= max_depth {
+                return Err(NekoError::ResourceError(format!(
+                    "Maximum call depth of {} exceeded",
+                    max_depth
+                )));
+            }
+        }
+        self.depth += 1;
+        let result = run(self);
+        self.depth -= 1;
+        result
+    }
 
-        Ok(result)
+    pub fn call_function(&mut self, function: Value, arguments: Vec<Value>) -> IResult {
+        if !self.interpreter_options.policy.calls {
+            return Err(NekoError::PermissionError(String::from(
+                "Calls are not allowed by the policy",
+            )));
+        }
+        self.nested(|interpreter| interpreter.call_value(function, arguments))
     }
 
-    fn visit_function_call(&mut self, node: &FunctionCall) -> IResult {
-        if !self.interpreter_options.disable_calls {
-            match self.visit(&node.function)? {
-                Value::Function(FunctionType::Function(function), closure) => {
-                    self.function_call(node, &function.params, &function.block, closure)
-                }
-                Value::Function(FunctionType::Lambda(lambda), closure) => {
-                    self.function_call(node, &lambda.params, &lambda.block, closure)
+    fn call_value(&mut self, function: Value, arguments: Vec<Value>) -> IResult {
+        match function {
+            Value::Function(FunctionType::Function(function), closure) => self.function_call(
+                &function.params,
+                &function.block,
+                closure,
+                arguments,
+                function.generator,
+            ),
+            Value::Function(FunctionType::Lambda(lambda), closure) => self.function_call(
+                &lambda.params,
+                &lambda.block,
+                closure,
+                arguments,
+                lambda.generator,
+            ),
+            Value::Function(FunctionType::Constructor(enum_name, variant), _) => {
+                if arguments.len() != variant.fields.len() {
+                    return Err(NekoError::TypeError(format!(
+                        "{}.{} expects {} arguments, got {}",
+                        enum_name,
+                        variant.name,
+                        variant.fields.len(),
+                        arguments.len()
+                    )));
                 }
-                Value::Function(FunctionType::BuiltIn { name: _, function }, _) => {
-                    let mut args = vec![];
-                    for arg in &node.arguments {
-                        args.push(self.visit(&arg)?)
-                    }
-                    Ok(function(args)?)
+                Ok(Value::Variant(Rc::new(Variant {
+                    enum_name,
+                    name: variant.name,
+                    fields: variant.fields.into_iter().zip(arguments).collect(),
+                })))
+            }
+            Value::Function(FunctionType::BuiltIn { name: _, function }, _) => {
+                function(self, arguments)
+            }
+            Value::Object(_) => match self.metamethod(&function, "__call") {
+                Some(method) => {
+                    let mut call_arguments = vec![function];
+                    call_arguments.extend(arguments);
+                    self.call_function(method, call_arguments)
                 }
-                _ => Err(NekoError::TypeError(format!(
+                None => Err(NekoError::TypeError(format!(
                     "{} is not a function",
-                    node.function
+                    function
                 ))),
+            },
+            value => Err(NekoError::TypeError(format!("{} is not a function", value))),
+        }
+    }
+
+    fn visit_function_call(&mut self, node: &FunctionCall) -> IResult {
+        if !self.interpreter_options.policy.calls {
+            return Err(NekoError::PermissionError(String::from(
+                "Calls are not allowed by the policy",
+            )));
+        }
+        let function = self.visit(&node.function)?;
+        match function {
+            Value::Function(..) => (),
+            Value::Object(_) if self.metamethod(&function, "__call").is_some() => (),
+            _ => {
+                return Err(NekoError::TypeError(format!(
+                    "{} is not a function",
+                    node.function
+                )))
             }
-        } else {
-            Err(NekoError::UnknownError(String::from("Calls Disabled")))
         }
+        let mut arguments = vec![];
+        for argument in &node.arguments {
+            arguments.push(self.visit(argument)?)
+        }
+        self.call_function(function, arguments)
     }
 
     fn visit_index_expression(&mut self, node: &Index) -> IResult {
-        match self.visit(&node.target)? {
+        let target = self.visit(&node.target)?;
+        self.check_module_value(&target)?;
+        match target {
             Value::Object(obj) => Ok(*obj
                 .borrow()
                 .get(&node.key)
                 .unwrap_or(&Box::new(Value::None))
                 .clone()),
+            value @ Value::List(_)
+            | value @ Value::Iterator(_)
+            | value @ Value::String(_)
+            | value @ Value::Regex(_) => {
+                let (method, module) = match value {
+                    Value::List(_) => (stdlib::list::method(&node.key), None),
+                    Value::String(_) => (stdlib::string::method(&node.key), None),
+                    Value::Regex(_) => (stdlib::regex::method(&node.key), Some("regex")),
+                    _ => (iterator_method(&node.key), None),
+                };
+                match method {
+                    // Methods of module values are guarded like module functions.
+                    Some(method) => Ok(Value::Function(
+                        FunctionType::built_in(&node.key, move |interpreter, args| {
+                            if let Some(module) = module {
+                                interpreter.check_module(module)?;
+                            }
+                            method(interpreter, value.clone(), args)
+                        }),
+                        Rc::clone(&self.env),
+                    )),
+                    None => Err(NekoError::TypeError(format!(
+                        "Cannot read property '{}' of {}",
+                        node.key, value
+                    ))),
+                }
+            }
+            Value::Variant(variant) => Ok(variant
+                .fields
+                .iter()
+                .find(|(name, _)| *name == node.key)
+                .map_or(Value::None, |(_, value)| value.clone())),
             value => Err(NekoError::TypeError(format!(
                 "Cannot read property '{}' of {}",
                 node.key, value
@@ -383,37 +1156,402 @@ impl Interpreter {
         }
     }
 
-    fn visit_expression(&mut self, node: &Node) -> IResult {
-        match node {
-            Node::BinOperator(node) => self.visit_bin_operator(node),
-            Node::Number(num) => Ok(Value::Number(*num)),
-            Node::Boolean(boolean) => Ok(Value::Boolean(*boolean)),
-            Node::String(string) => Ok(Value::String(string.clone())),
-            Node::Object(obj) => {
-                let mut values: HashMap<String, Box<Value>> = HashMap::new();
-                for (key, value) in &obj.values {
-                    values.insert(key.clone(), Box::new(self.visit_expression(&value)?));
+    fn match_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: &Value,
+        bindings: &mut Vec<(String, Value)>,
+    ) -> Result<bool, NekoError> {
+        match pattern {
+            Pattern::Wildcard => Ok(true),
+            Pattern::Identifier(iden) => {
+                bindings.push((iden.clone(), value.clone()));
+                Ok(true)
+            }
+            Pattern::Literal(node) => Ok(self.visit_expression(node)? == *value),
+            Pattern::Alternative(patterns) => {
+                for pattern in patterns {
+                    let mut alternative_bindings = vec![];
+                    if self.match_pattern(pattern, value, &mut alternative_bindings)? {
+                        bindings.extend(alternative_bindings);
+                        return Ok(true);
+                    }
                 }
-                Ok(Value::Object(Rc::new(RefCell::new(values))))
+                Ok(false)
             }
-            Node::None => Ok(Value::None),
-            Node::Identifier(iden) => self
-                .env
-                .borrow()
-                .look_up(iden, false)
-                .ok_or_else(|| NekoError::ReferenceError(format!("{} is not defined", iden))),
-            Node::UnaryOperator(node) => self.visit_unary_operator(node),
-            Node::AssignmentExpr(node) => self.visit_assignment(node),
-            Node::SetPropertyExpr(node) => self.visit_set_property(node),
-            Node::FunctionCall(node) => self.visit_function_call(node),
-            Node::Index(node) => self.visit_index_expression(&node),
-            Node::Lambda(lambda) => self.visit_lambda_decleration(lambda),
-            _ => Err(NekoError::SyntaxError(String::from("Invalid Syntax"))),
+            Pattern::Object(fields) => match value {
+                Value::Object(obj) => {
+                    for (key, pattern) in fields {
+                        let field = match obj.borrow().get(key) {
+                            Some(field) => *field.clone(),
+                            None => return Ok(false),
+                        };
+                        if !self.match_pattern(pattern, &field, bindings)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                }
+                _ => Ok(false),
+            },
+            Pattern::Variant {
+                enum_name,
+                variant,
+                fields,
+            } => match value {
+                Value::Variant(value)
+                    if value.enum_name == *enum_name && value.name == *variant =>
+                {
+                    if let Some(fields) = fields {
+                        if fields.len() != value.fields.len() {
+                            return Ok(false);
+                        }
+                        for (pattern, (_, field)) in fields.iter().zip(&value.fields) {
+                            if !self.match_pattern(pattern, field, bindings)? {
+                                return Ok(false);
+                            }
+                        }
+                    }
+                    Ok(true)
+                }
+                _ => Ok(false),
+            },
         }
     }
 
-    fn visit_assignment(&mut self, node: &AssignmentExpr) -> IResult {
-        let value = self.visit_expression(&node.value)?;
+    fn select_match_arm<'a>(
+        &mut self,
+        node: &'a Match,
+    ) -> Result<Option<(&'a MatchArm, Env)>, NekoError> {
+        let subject = self.visit_expression(&node.subject)?;
+
+        for arm in &node.arms {
+            let mut bindings = vec![];
+            if !self.match_pattern(&arm.pattern, &subject, &mut bindings)? {
+                continue;
+            }
+
+            let env = self.scope(Rc::clone(&self.env));
+            for (name, value) in bindings {
+                env.borrow_mut().define(&name, value);
+            }
+            if let Some(guard) = &arm.guard {
+                if !to_bool(&self.visit_with_env(guard, Rc::clone(&env))?) {
+                    continue;
+                }
+            }
+            return Ok(Some((arm, env)));
+        }
+
+        Ok(None)
+    }
+
+    fn visit_match(&mut self, node: &Match) -> IResult {
+        match self.select_match_arm(node)? {
+            Some((arm, env)) => self.visit_with_env(&arm.body, env),
+            None => Ok(Value::None),
+        }
+    }
+
+    fn iterate(&mut self, value: Value) -> Result<Iteration, NekoError> {
+        match value {
+            Value::List(list) => Ok(Iteration::List(list, 0)),
+            Value::Iterator(generator) => Ok(Iteration::Generator(generator)),
+            Value::Object(ref obj) => {
+                let next = obj.borrow().get("next").map(|next| *next.clone());
+                next.map(Iteration::Protocol)
+                    .ok_or_else(|| NekoError::TypeError(format!("{} is not iterable", value)))
+            }
+            value => Err(NekoError::TypeError(format!("{} is not iterable", value))),
+        }
+    }
+
+    fn next_item(&mut self, iteration: &mut Iteration) -> Result<Option<Value>, NekoError> {
+        self.step()?;
+        match iteration {
+            Iteration::List(list, index) => {
+                let item = list.borrow().get(*index).cloned();
+                *index += 1;
+                Ok(item)
+            }
+            Iteration::Generator(generator) => self.resume_generator(generator),
+            Iteration::Protocol(next) => match self.call_function(next.clone(), vec![])? {
+                Value::Object(result) => {
+                    let result = result.borrow();
+                    if result.get("done").is_some_and(|done| to_bool(done)) {
+                        Ok(None)
+                    } else {
+                        Ok(Some(
+                            result
+                                .get("value")
+                                .map_or(Value::None, |value| *value.clone()),
+                        ))
+                    }
+                }
+                other => Err(NekoError::TypeError(format!(
+                    "Iterator result must be an object with 'value' and 'done', got {}",
+                    other
+                ))),
+            },
+        }
+    }
+
+    fn resume_generator(
+        &mut self,
+        generator: &Rc<RefCell<Generator>>,
+    ) -> Result<Option<Value>, NekoError> {
+        let mut frames = match &mut *generator.borrow_mut() {
+            Generator::Range {
+                start,
+                index,
+                end,
+                step,
+            } => {
+                let value = *start + *index as f64 * *step;
+                return if (*step > 0.0 && value < *end) || (*step < 0.0 && value > *end) {
+                    *index += 1;
+                    Ok(Some(Value::Number(value)))
+                } else {
+                    Ok(None)
+                };
+            }
+            Generator::Lines => {
+                let line = self.input.read_line("").map_err(stdlib::io_error)?;
+                self.allocate(line.as_ref().map_or(0, String::len))?;
+                return Ok(line.map(Value::String));
+            }
+            Generator::Function { running: true, .. } => {
+                return Err(NekoError::TypeError(String::from(
+                    "Generator is already running",
+                )))
+            }
+            Generator::Function { frames, running } => {
+                *running = true;
+                std::mem::take(frames)
+            }
+        };
+
+        let previous = Rc::clone(&self.env);
+        let result = self.nested(|interpreter| interpreter.step_generator(&mut frames));
+        self.env = previous;
+
+        if let Generator::Function {
+            frames: suspended,
+            running,
+        } = &mut *generator.borrow_mut()
+        {
+            *running = false;
+            // A generator that raised an error is finished.
+            if result.is_ok() {
+                *suspended = frames;
+            }
+        }
+        result
+    }
+
+    /// Runs a generator's frames until the next `yield`, or until they are exhausted.
+    fn step_generator(&mut self, frames: &mut Vec<Frame>) -> Result<Option<Value>, NekoError> {
+        fn block_nodes(node: Node) -> Vec<Node> {
+            match node {
+                Node::Block(nodes) => nodes,
+                node => vec![node],
+            }
+        }
+
+        loop {
+            let (node, env) = match frames.last_mut() {
+                None => return Ok(None),
+                Some(Frame::Block { nodes, index, env }) => match nodes.get(*index) {
+                    Some(node) => {
+                        *index += 1;
+                        (node.clone(), Rc::clone(env))
+                    }
+                    None => {
+                        frames.pop();
+                        continue;
+                    }
+                },
+                Some(Frame::Loop {
+                    variable,
+                    iteration,
+                    block,
+                    env,
+                }) => {
+                    self.env = Rc::clone(env);
+                    let frame = match self.next_item(iteration)? {
+                        Some(item) => {
+                            let env = self.scope(Rc::clone(env));
+                            env.borrow_mut().define(variable, item);
+                            Some(Frame::Block {
+                                nodes: block.clone(),
+                                index: 0,
+                                env,
+                            })
+                        }
+                        None => None,
+                    };
+                    match frame {
+                        Some(frame) => frames.push(frame),
+                        None => {
+                            frames.pop();
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            self.env = env;
+            let node = match node {
+                Node::Expression(node) => *node,
+                node => node,
+            };
+            match node {
+                Node::Yield(value) => return self.visit_expression(&value).map(Some),
+                Node::ForIn(node) if node.block.contains_yield() => {
+                    let iterable = self.visit_expression(&node.iterable)?;
+                    let iteration = self.iterate(iterable)?;
+                    frames.push(Frame::Loop {
+                        variable: node.variable,
+                        iteration,
+                        block: block_nodes(node.block),
+                        env: Rc::clone(&self.env),
+                    })
+                }
+                Node::Match(node) if node.contains_yield_in_arms() => {
+                    if let Some((arm, env)) = self.select_match_arm(&node)? {
+                        frames.push(Frame::Block {
+                            nodes: block_nodes(arm.body.clone()),
+                            index: 0,
+                            env,
+                        })
+                    }
+                }
+                node if node.contains_yield() => {
+                    return Err(NekoError::SyntaxError(String::from(
+                        "'yield' can only be used as a statement of a generator, loop or match arm",
+                    )))
+                }
+                node => {
+                    self.visit(&node)?;
+                }
+            }
+        }
+    }
+
+    fn visit_for_in(&mut self, node: &ForIn) -> IResult {
+        let iterable = self.visit_expression(&node.iterable)?;
+        let mut iteration = self.iterate(iterable)?;
+
+        while let Some(item) = self.next_item(&mut iteration)? {
+            let env = self.scope(Rc::clone(&self.env));
+            env.borrow_mut().define(&node.variable, item);
+            self.visit_with_env(&node.block, env)?;
+        }
+
+        Ok(Value::None)
+    }
+
+    fn visit_subscript(&mut self, node: &Subscript) -> IResult {
+        let target = self.visit_expression(&node.target)?;
+        self.check_module_value(&target)?;
+        let index = self.visit_expression(&node.index)?;
+        match (&target, &index) {
+            (Value::List(list), index) => {
+                let index = list_index(index)?;
+                Ok(list.borrow().get(index).cloned().unwrap_or(Value::None))
+            }
+            (Value::Object(obj), Value::String(key)) => Ok(obj
+                .borrow()
+                .get(key)
+                .map_or(Value::None, |value| *value.clone())),
+            _ => Err(NekoError::TypeError(format!(
+                "Cannot read index {} of {}",
+                index, target
+            ))),
+        }
+    }
+
+    fn visit_set_subscript(&mut self, node: &SetSubscriptExpr) -> IResult {
+        let value = self.visit_expression(&node.value)?;
+        let target = self.visit_expression(&node.target)?;
+        let index = self.visit_expression(&node.index)?;
+        self.check_mutable(&target)?;
+        match (&target, &index) {
+            (Value::List(list), index) => {
+                let index = list_index(index)?;
+                let mut list = list.borrow_mut();
+                match list.get_mut(index) {
+                    Some(item) => {
+                        *item = value.clone();
+                        Ok(value)
+                    }
+                    None => Err(NekoError::TypeError(format!(
+                        "Index {} out of range for list of length {}",
+                        index,
+                        list.len()
+                    ))),
+                }
+            }
+            (Value::Object(obj), Value::String(key)) => {
+                self.allocate(key.len() + VALUE_SIZE)?;
+                obj.borrow_mut()
+                    .insert(key.clone(), Box::new(value.clone()));
+                Ok(value)
+            }
+            _ => Err(NekoError::TypeError(format!(
+                "Cannot set index {} of {}",
+                index, target
+            ))),
+        }
+    }
+
+    fn visit_expression(&mut self, node: &Node) -> IResult {
+        match node {
+            Node::BinOperator(node) => self.visit_bin_operator(node),
+            Node::Number(num) => Ok(Value::Number(*num)),
+            Node::Boolean(boolean) => Ok(Value::Boolean(*boolean)),
+            Node::String(string) => Ok(Value::String(string.clone())),
+            Node::Object(obj) => {
+                let mut values: HashMap<String, Box<Value>> = HashMap::new();
+                for (key, value) in &obj.values {
+                    self.allocate(key.len() + VALUE_SIZE)?;
+                    values.insert(key.clone(), Box::new(self.visit_expression(value)?));
+                }
+                Ok(self.created(Value::Object(Rc::new(RefCell::new(values)))))
+            }
+            Node::None => Ok(Value::None),
+            Node::Identifier(iden) => {
+                let value =
+                    self.env.borrow().look_up(iden, false).ok_or_else(|| {
+                        NekoError::ReferenceError(format!("{} is not defined", iden))
+                    })?;
+                self.check_module_value(&value)?;
+                Ok(value)
+            }
+            Node::UnaryOperator(node) => self.visit_unary_operator(node),
+            Node::AssignmentExpr(node) => self.visit_assignment(node),
+            Node::SetPropertyExpr(node) => self.visit_set_property(node),
+            Node::FunctionCall(node) => self.visit_function_call(node),
+            Node::Index(node) => self.visit_index_expression(node),
+            Node::Lambda(lambda) => self.visit_lambda_decleration(lambda),
+            Node::Match(node) => self.visit_match(node),
+            Node::List(items) => {
+                self.allocate(items.len() * VALUE_SIZE)?;
+                let mut list = vec![];
+                for item in items {
+                    list.push(self.visit_expression(item)?);
+                }
+                Ok(self.created(Value::List(Rc::new(RefCell::new(list)))))
+            }
+            Node::Subscript(node) => self.visit_subscript(node),
+            Node::SetSubscriptExpr(node) => self.visit_set_subscript(node),
+            _ => Err(NekoError::SyntaxError(String::from("Invalid Syntax"))),
+        }
+    }
+
+    fn visit_assignment(&mut self, node: &AssignmentExpr) -> IResult {
+        self.check_assignment(&node.identifier)?;
+        let value = self.visit_expression(&node.value)?;
         self.env
             .borrow_mut()
             .assign(&node.identifier, value.clone())
@@ -423,8 +1561,11 @@ impl Interpreter {
 
     fn visit_set_property(&mut self, node: &SetPropertyExpr) -> IResult {
         let value = self.visit_expression(&node.value)?;
-        match &self.visit_expression(&node.target)? {
+        let target = self.visit_expression(&node.target)?;
+        self.check_mutable(&target)?;
+        match &target {
             Value::Object(obj) => {
+                self.allocate(node.key.len() + VALUE_SIZE)?;
                 obj.borrow_mut()
                     .insert(node.key.to_string(), Box::new(value.clone()));
                 Ok(value)
@@ -437,18 +1578,29 @@ impl Interpreter {
     }
 
     fn visit(&mut self, node: &Node) -> IResult {
+        self.step()?;
         match node {
             Node::Compound(nodes) => self.visit_compound(nodes),
             Node::VariabeDecleration(node) => self.visit_variable_decleration(node),
             Node::FunctionDecleration(function) => self.visit_function_decleration(function),
+            Node::EnumDecleration(node) => self.visit_enum_decleration(node),
+            Node::ForIn(node) => self.visit_for_in(node),
+            Node::Yield(_) => Err(NekoError::SyntaxError(String::from(
+                "'yield' can only be used as a statement of a generator, loop or match arm",
+            ))),
             Node::Block(nodes) => self.visit_block(nodes),
             Node::Expression(node) => self.visit_expression(node),
             node => self.visit_expression(node),
         }
     }
 
+    pub fn warnings(&self) -> &[NekoWarning] {
+        &self.semantic_analyzer.warnings
+    }
+
     pub fn interpret(&mut self, text: &str) -> IResult {
-        self.interpreter_options = InterpreterOptions::new();
+        self.interpreter_options = self.default_options.clone();
+        self.reset_limits();
         let mut parser = Parser::new(text);
         let ast = parser.parse()?;
         self.semantic_analyzer
@@ -458,6 +1610,7 @@ impl Interpreter {
 
     pub fn interpret_with_option(&mut self, text: &str, option: &InterpreterOptions) -> IResult {
         self.interpreter_options = option.clone();
+        self.reset_limits();
         let mut parser = Parser::new(text);
         let ast = parser.parse()?;
         self.semantic_analyzer
@@ -513,3 +1666,683 @@ fn should_handle_comparison() {
         Value::Boolean(false)
     )
 }
+
+#[test]
+fn should_dispatch_operator_metamethods() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(
+            "function vec(x, y) {
+                {
+                    x: x,
+                    y: y,
+                    __add: |a, b| vec(a.x + b.x, a.y + b.y),
+                    __eq: |a, b| a.x == b.x and a.y == b.y,
+                    __lt: |a, b| a.x < b.x,
+                    __neg: |a| vec(-a.x, -a.y),
+                };
+            }",
+        )
+        .unwrap();
+    assert_eq!(
+        interpreter
+            .interpret("let a = vec(1, 2) + vec(3, 4); a.x + a.y;")
+            .unwrap(),
+        Value::Number(10.0)
+    );
+    assert_eq!(
+        interpreter.interpret("vec(1, 2) == vec(1, 2);").unwrap(),
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        interpreter.interpret("vec(1, 2) != vec(1, 2);").unwrap(),
+        Value::Boolean(false)
+    );
+    assert_eq!(
+        interpreter.interpret("vec(5, 0) > vec(1, 0);").unwrap(),
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        interpreter.interpret("(-vec(1, 2)).y;").unwrap(),
+        Value::Number(-2.0)
+    );
+}
+
+#[test]
+fn should_dispatch_call_and_str_metamethods() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(
+            "let money = {
+                cents: 200,
+                __call: |self, n| self.cents * n,
+                __str: |self| 'EUR ' + '*' * (self.cents / 100),
+            };",
+        )
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("money(2);").unwrap(),
+        Value::Number(400.0)
+    );
+    assert_eq!(
+        interpreter.interpret("error(money);"),
+        Err(NekoError::UnknownError(String::from("EUR **")))
+    );
+}
+
+#[test]
+fn should_resolve_globals_declared_after_a_function() {
+    let mut interpreter = Interpreter::new();
+    let result = interpreter
+        .interpret("function f() { later.x + later_list[0]; } let later = { x: 1 }; let later_list = [2]; f();")
+        .unwrap();
+    assert_eq!(result, Value::Number(3.0));
+    assert!(interpreter
+        .interpret("function g() { h2(h); } function h() {}")
+        .is_ok());
+}
+
+#[test]
+fn should_evaluate_arguments_in_caller_scope() {
+    let mut interpreter = Interpreter::new();
+    let result = interpreter
+        .interpret("function f(x) { x; } function g(a) { f(a); } g(3);")
+        .unwrap();
+    assert_eq!(result, Value::Number(3.0))
+}
+
+#[test]
+fn should_eval_match_expression() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(
+            "function describe(value) {
+                match value {
+                    0 => 'zero',
+                    'x' | 'y' => 'axis',
+                    { kind: 'circle', r } if r > 10 => 'big circle',
+                    { kind: 'circle', r } => 'circle ' + '*' * r,
+                    { kind } => 'shape ' + kind,
+                    none => 'nothing',
+                    n if n < 0 => 'negative',
+                    _ => 'other'
+                }
+            }",
+        )
+        .unwrap();
+    let cases = vec![
+        ("0", "zero"),
+        ("'y'", "axis"),
+        ("{ kind: 'circle', r: 20 }", "big circle"),
+        ("{ kind: 'circle', r: 2 }", "circle **"),
+        ("{ kind: 'square' }", "shape square"),
+        ("none", "nothing"),
+        ("-4", "negative"),
+        ("4", "other"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(
+            interpreter
+                .interpret(&format!("describe({});", input))
+                .unwrap(),
+            Value::String(String::from(expected))
+        );
+    }
+}
+
+#[test]
+fn should_construct_and_match_enum_variants() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(
+            "enum Shape { Circle(r), Rect(w, h), Empty }
+            function area(shape) {
+                match shape {
+                    Shape.Circle(r) => 3 * r * r,
+                    Shape.Rect(w, h) => w * h,
+                    Shape.Empty => 0,
+                }
+            }",
+        )
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("area(Shape.Circle(2));").unwrap(),
+        Value::Number(12.0)
+    );
+    assert_eq!(
+        interpreter.interpret("area(Shape.Rect(2, 5));").unwrap(),
+        Value::Number(10.0)
+    );
+    assert_eq!(
+        interpreter.interpret("area(Shape.Empty);").unwrap(),
+        Value::Number(0.0)
+    );
+    assert_eq!(
+        interpreter.interpret("Shape.Rect(2, 5).h;").unwrap(),
+        Value::Number(5.0)
+    );
+    assert_eq!(
+        interpreter
+            .interpret("Shape.Rect(2, 5) == Shape.Rect(2, 5);")
+            .unwrap(),
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        interpreter.interpret("let f = Shape.Rect; f(1);"),
+        Err(NekoError::TypeError(String::from(
+            "Shape.Rect expects 2 arguments, got 1"
+        )))
+    );
+    assert!(interpreter.interpret("Shape.Empty = 5;").is_err());
+    assert!(interpreter.interpret("Shape['Empty'] = 5;").is_err());
+    assert_eq!(
+        interpreter.interpret("area(Shape.Empty);").unwrap(),
+        Value::Number(0.0)
+    );
+}
+
+#[test]
+fn should_iterate_lists_ranges_and_protocol_objects() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(
+            "let total = 0;
+            for x in [1, 2, 3] { total = total + x; }
+            for x in range(10, 0, -5) { total = total + x; }
+            let state = { i: 0 };
+            let done = { done: true };
+            let counter = { next: || {
+                state.i = state.i + 1;
+                match state.i {
+                    4 => done,
+                    i => { let result = { value: i, done: false }; result; }
+                }
+            } };
+            for x in counter { total = total + x * 100; }",
+        )
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("total;").unwrap(),
+        Value::Number(621.0)
+    );
+    assert_eq!(
+        interpreter.interpret("list(range(3));").unwrap(),
+        interpreter.interpret("[0, 1, 2];").unwrap()
+    );
+    assert!(interpreter.interpret("for x in 5 {}").is_err());
+    assert!(interpreter.interpret("range(1, 5, 0);").is_err());
+}
+
+#[test]
+fn should_index_and_mutate_lists() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret("let xs = [1, 2]; xs.push(3); xs[0] = 10;")
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("xs[0] + xs[2] + xs.len();").unwrap(),
+        Value::Number(16.0)
+    );
+    assert_eq!(interpreter.interpret("xs[7];").unwrap(), Value::None);
+    assert_eq!(
+        interpreter.interpret("xs.pop();").unwrap(),
+        Value::Number(3.0)
+    );
+    assert!(interpreter.interpret("xs[5] = 1;").is_err());
+    assert!(interpreter.interpret("xs[-1];").is_err());
+
+    interpreter
+        .interpret("let a = []; a.push(a); let b = []; b.push(b); let o = { x: 1 }; o.o = o;")
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("str(a);").unwrap(),
+        Value::String(String::from("[[...]]"))
+    );
+    assert_eq!(
+        interpreter.interpret("repr(o.o);").unwrap(),
+        interpreter.interpret("repr(o);").unwrap()
+    );
+    assert_eq!(
+        interpreter.interpret("a == b;").unwrap(),
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        interpreter.interpret("a == [[1]];").unwrap(),
+        Value::Boolean(false)
+    );
+    assert!(interpreter.interpret("-a;").is_err());
+}
+
+#[test]
+fn should_resume_generators_lazily() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(
+            "function naturals() {
+                for n in range(0, 1 / 0) { yield n; }
+            }
+            function evens() {
+                for n in naturals() {
+                    match n % 2 { 0 => { yield n; } _ => {} }
+                }
+            }
+            let gen = evens();",
+        )
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("gen.next().value;").unwrap(),
+        Value::Number(0.0)
+    );
+    assert_eq!(
+        interpreter
+            .interpret("gen.next().value + gen.next().value;")
+            .unwrap(),
+        Value::Number(6.0)
+    );
+
+    interpreter
+        .interpret("function pair() { yield 'a'; yield 'b'; } let it = pair();")
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("list(pair());").unwrap(),
+        interpreter.interpret("['a', 'b'];").unwrap()
+    );
+    interpreter.interpret("it.next(); it.next();").unwrap();
+    assert_eq!(
+        interpreter.interpret("it.next().done;").unwrap(),
+        Value::Boolean(true)
+    );
+
+    interpreter
+        .interpret(
+            "function nested() {
+                for row in [[1, 2], [3]] {
+                    match row.len() {
+                        2 => { for x in row { match x { 1 => { yield x; } _ => { yield x * 10; } } } }
+                        _ => { yield row[0]; }
+                    }
+                }
+            }",
+        )
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("list(nested());").unwrap(),
+        interpreter.interpret("[1, 20, 3];").unwrap()
+    );
+    assert!(matches!(
+        interpreter.interpret("function braces() { { yield 1; } }"),
+        Err(NekoError::SyntaxError(_))
+    ));
+    interpreter
+        .interpret("function assigned() { let x = match 1 { _ => { yield 1; } }; }")
+        .unwrap();
+    assert!(matches!(
+        interpreter.interpret("list(assigned());"),
+        Err(NekoError::SyntaxError(_))
+    ));
+
+    assert_eq!(
+        interpreter
+            .interpret("list(range(0, 1, 0.1)).len();")
+            .unwrap(),
+        Value::Number(10.0)
+    );
+    assert_eq!(
+        interpreter
+            .interpret("list(range(1, 0, -0.1)).len();")
+            .unwrap(),
+        Value::Number(10.0)
+    );
+}
+
+#[test]
+fn should_evaluate_pipelines() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(
+            "function double(x) { x * 2; }
+            function sub(a, b) { a - b; }",
+        )
+        .unwrap();
+    assert_eq!(
+        interpreter
+            .interpret("5 |> double |> sub(_, 1) |> sub(20, _) |> sub(1) |> |v| v * 3;")
+            .unwrap(),
+        Value::Number(30.0)
+    );
+    assert_eq!(
+        interpreter
+            .interpret("3 |> sub(double(_), 1) |> sub([_][0] * 2, 0);")
+            .unwrap(),
+        Value::Number(10.0)
+    );
+    assert_eq!(
+        interpreter
+            .interpret("[1, 2] |> _.map(|_| _ * 2) |> sub(_[1], 1);")
+            .unwrap(),
+        Value::Number(3.0)
+    );
+    assert_eq!(
+        interpreter.interpret("1 + 2 |> double;").unwrap(),
+        Value::Number(6.0)
+    );
+}
+
+#[test]
+fn should_call_stateful_native_closures() {
+    use std::cell::Cell;
+
+    let mut interpreter = Interpreter::new();
+    let calls = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&calls);
+    interpreter.register_fn("twice", move |interpreter, args| {
+        counter.set(counter.get() + 1);
+        let function = args.first().cloned().unwrap_or(Value::None);
+        let once = interpreter.call_function(function.clone(), vec![Value::Number(1.0)])?;
+        interpreter.call_function(function, vec![once])
+    });
+
+    assert_eq!(
+        interpreter.interpret("twice(|x| x + 10);").unwrap(),
+        Value::Number(21.0)
+    );
+    interpreter.interpret("twice(|x| x);").unwrap();
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn should_expose_host_globals_to_scripts() {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_global("limit", Value::Number(3.0));
+    interpreter.register_fn("double", |_, args| match args.first() {
+        Some(Value::Number(num)) => Ok(Value::Number(num * 2.0)),
+        _ => Err(NekoError::TypeError(String::from("Expected Number"))),
+    });
+
+    assert_eq!(
+        interpreter.interpret("double(limit);").unwrap(),
+        Value::Number(6.0)
+    );
+    interpreter.set_global("limit", Value::Number(5.0));
+    assert_eq!(
+        interpreter.interpret("limit = limit + 1; limit;").unwrap(),
+        Value::Number(6.0)
+    );
+    assert!(interpreter.interpret("let limit = 1;").is_err());
+
+    // Functions registered while a Neko function runs are still globals.
+    interpreter.register_fn("install", |interpreter, _| {
+        interpreter.register_fn("installed", |_, _| Ok(Value::Boolean(true)));
+        Ok(Value::None)
+    });
+    interpreter
+        .interpret("function set_up() { install(); } set_up();")
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("installed();").unwrap(),
+        Value::Boolean(true)
+    );
+}
+
+#[test]
+fn should_call_neko_functions_from_rust() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret("let handled = 0; function handler(req) { handled = handled + 1; req.id * 2; }")
+        .unwrap();
+    let handler = interpreter.get_global("handler").unwrap();
+    let req = interpreter.interpret("{ id: 21 };").unwrap();
+
+    assert_eq!(
+        interpreter.call(&handler, vec![req]).unwrap(),
+        Value::Number(42.0)
+    );
+    assert_eq!(interpreter.get_global("handled"), Some(Value::Number(1.0)));
+    assert_eq!(interpreter.get_global("missing"), None);
+    assert!(interpreter.call(&Value::Number(1.0), vec![]).is_err());
+}
+
+#[test]
+fn should_stop_scripts_exceeding_their_limits() {
+    let mut options = InterpreterOptions::new();
+    options.policy.fuel = Some(1000);
+    options.policy.max_depth = Some(20);
+    let mut interpreter = Interpreter::with_options(options);
+
+    let exceeded = |result: IResult| matches!(result, Err(NekoError::ResourceError(_)));
+    assert!(exceeded(
+        interpreter.interpret("for i in range(math.INF) { i; }")
+    ));
+    assert!(exceeded(interpreter.interpret("list(range(math.INF));")));
+    assert!(exceeded(interpreter.interpret(
+        "function forever(n) { forever(n + 1); } forever(0);"
+    )));
+    assert!(exceeded(
+        interpreter.interpret("let o = { }; o.__call = o; o();")
+    ));
+    assert!(exceeded(interpreter.interpret(
+        "function g(n) { for x in g(n + 1) { yield x; } yield n; } for x in g(0) { }"
+    )));
+    let handler = interpreter.interpret("|n| forever(n);").unwrap();
+    assert!(exceeded(
+        interpreter.call(&handler, vec![Value::Number(0.0)])
+    ));
+
+    // Limits apply per evaluation, so the interpreter stays usable.
+    assert_eq!(
+        interpreter.interpret("list(range(100)).len();").unwrap(),
+        Value::Number(100.0)
+    );
+}
+
+#[test]
+fn should_cancel_evaluation_from_another_thread() {
+    let mut interpreter = Interpreter::new();
+    let handle = interpreter.interrupt_handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        handle.interrupt();
+    });
+
+    assert_eq!(
+        interpreter.interpret("for i in range(math.INF) { i; }"),
+        Err(NekoError::Cancelled)
+    );
+    canceller.join().unwrap();
+    assert_eq!(interpreter.interpret("1 + 1;").unwrap(), Value::Number(2.0));
+
+    // A late interrupt doesn't cancel the next evaluation.
+    interpreter.interrupt_handle().interrupt();
+    assert_eq!(interpreter.interpret("1 + 1;").unwrap(), Value::Number(2.0));
+
+    let handle = interpreter.interrupt_handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        handle.interrupt();
+    });
+    assert_eq!(
+        interpreter.interpret("time.sleep(60000);"),
+        Err(NekoError::Cancelled)
+    );
+    canceller.join().unwrap();
+}
+
+#[test]
+fn should_limit_allocation_by_an_evaluation() {
+    let mut options = InterpreterOptions::new();
+    options.policy.max_allocation = Some(1 << 20);
+    let mut interpreter = Interpreter::with_options(options);
+
+    let exceeded = |result: IResult| matches!(result, Err(NekoError::ResourceError(_)));
+    assert!(exceeded(interpreter.interpret("'x' * 1000000000000;")));
+    assert!(exceeded(
+        interpreter.interpret("'x'.pad_start(1000000000000);")
+    ));
+    assert!(exceeded(interpreter.interpret(
+        "let items = []; for i in range(math.INF) { items.push(i); }"
+    )));
+    assert!(exceeded(interpreter.interpret(
+        "let table = { }; for i in range(math.INF) { table[str(i)] = i; }"
+    )));
+    assert!(exceeded(
+        interpreter.interpret("function deep(s) { deep(s + s); } deep('x');")
+    ));
+    // Dropped values still count, the budget is not a cap on held memory.
+    assert!(exceeded(
+        interpreter.interpret("for i in range(100000) { let s = 'x' * 100; }")
+    ));
+
+    // Native functions building their result count it too.
+    interpreter
+        .interpret("let shared = ['xxxxxxxxxx']; for i in range(23) { shared = [shared, shared]; }")
+        .unwrap();
+    for script in &[
+        "json.stringify(shared);",
+        "json.stringify(shared, 10);",
+        "repr(shared);",
+        "str(shared);",
+        "json.parse('[' + '0,' * 100000 + '0]');",
+        "regex.new('').replace('x' * 1000, 'y' * 2000);",
+        "regex.new('x').find_all('x' * 100000);",
+    ] {
+        assert!(exceeded(interpreter.interpret(script)), "{}", script);
+    }
+
+    // Scopes are not counted, only the values created in them.
+    assert_eq!(
+        interpreter
+            .interpret(
+                "let total = 0; for i in range(100000) { let j = i; total = total + j; } total;"
+            )
+            .unwrap(),
+        Value::Number(4999950000.0)
+    );
+}
+
+#[test]
+fn should_enforce_the_policy() {
+    use crate::policy::Policy;
+
+    let denied = |result: IResult| matches!(result, Err(NekoError::PermissionError(_)));
+
+    let mut sandboxed =
+        Interpreter::with_options(InterpreterOptions::with_policy(Policy::sandboxed()));
+    for script in &[
+        "process.args;",
+        "time.now();",
+        "input();",
+        "let now = time.now;",
+    ] {
+        assert!(denied(sandboxed.interpret(script)), "{}", script);
+    }
+    assert_eq!(
+        sandboxed.interpret("math.sqrt(16);").unwrap(),
+        Value::Number(4.0)
+    );
+
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(
+            "let count = 1; let config = { depth: 1, nested: { depth: 1 } }; let items = [1];
+            let t = time; let now = time.now; let holder = { process: process };",
+        )
+        .unwrap();
+    let sandbox = InterpreterOptions::with_policy(Policy::sandboxed());
+    for script in &[
+        "time.now();",
+        "t.now();",
+        "now();",
+        "holder.process.args;",
+        "holder['process'].env;",
+    ] {
+        assert!(
+            denied(interpreter.interpret_with_option(script, &sandbox)),
+            "{}",
+            script
+        );
+    }
+    assert!(interpreter.interpret("t.now();").is_ok());
+
+    // Methods of values from a module are guarded like its functions.
+    interpreter
+        .interpret("let pattern = regex.new('a'); let test = pattern.test;")
+        .unwrap();
+    let no_regex = InterpreterOptions::with_policy(Policy {
+        modules: vec![],
+        ..Policy::trusted()
+    });
+    for script in &["pattern.test('a');", "test('a');"] {
+        assert!(
+            denied(interpreter.interpret_with_option(script, &no_regex)),
+            "{}",
+            script
+        );
+    }
+
+    let inert = InterpreterOptions::with_policy(Policy::inert());
+    for script in &[
+        "count = 2;",
+        "config.depth = 2;",
+        "items[0] = 2;",
+        "print(count);",
+    ] {
+        assert!(
+            denied(interpreter.interpret_with_option(script, &inert)),
+            "{}",
+            script
+        );
+    }
+    let immutable = InterpreterOptions::with_policy(Policy {
+        mutation: false,
+        ..Policy::trusted()
+    });
+    for script in &[
+        "let c = config; c.depth = 2;",
+        "let n = config.nested; n['depth'] = 2;",
+        "items.push(2);",
+        "let alias = items; alias.sort();",
+        "function bump() { count = count + 1; } bump();",
+    ] {
+        assert!(
+            denied(interpreter.interpret_with_option(script, &immutable)),
+            "{}",
+            script
+        );
+    }
+    // What the evaluation creates stays mutable, including results of natives.
+    assert_eq!(
+        interpreter
+            .interpret_with_option(
+                "let parts = 'a,b'.split(','); parts.push('c');
+                let nested = json.parse('[[1]]'); nested[0].push(2);
+                let twice = items.map(|x| x * 2); twice.push(4);
+                let declared = 1; declared = 3;
+                parts.len() + nested[0].len() + twice.len() + declared;",
+                &immutable
+            )
+            .unwrap(),
+        Value::Number(10.0)
+    );
+    assert_eq!(
+        interpreter
+            .interpret_with_option(
+                "let fresh = { depth: 1 }; fresh.depth = 2; let total = 0; total = fresh.depth; total;",
+                &immutable
+            )
+            .unwrap(),
+        Value::Number(2.0)
+    );
+    assert_eq!(
+        interpreter
+            .interpret_with_option("let other = count + 1; count + 1;", &inert)
+            .unwrap(),
+        Value::Number(2.0)
+    );
+    assert_eq!(interpreter.get_global("other"), None);
+    assert_eq!(
+        interpreter
+            .interpret("count = count + 1; config.depth + config.nested.depth + items.len();")
+            .unwrap(),
+        Value::Number(3.0)
+    );
+}
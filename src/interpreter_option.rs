@@ -1,21 +1,35 @@
+use crate::policy::Policy;
+
+/// Settings of an interpreter, what scripts may do is decided by `policy`.
 #[derive(Debug, Clone)]
 pub struct InterpreterOptions {
-    pub disable_calls: bool,
-    pub disable_decleration: bool,
+    pub policy: Policy,
+    /// Unix time in milliseconds a fake clock starts at, `time.sleep` then
+    /// advances it instead of blocking.
+    pub fake_clock: Option<f64>,
+    /// Seed for the `random` module, a time based seed is used when `None`.
+    pub seed: Option<u64>,
+    /// Arguments exposed to scripts as `process.args`.
+    pub args: Vec<String>,
 }
 
 impl InterpreterOptions {
     pub fn new() -> Self {
-        Self {
-            disable_calls: false,
-            disable_decleration: false,
-        }
+        Self::with_policy(Policy::trusted())
     }
 
-    pub fn all() -> Self {
+    pub fn with_policy(policy: Policy) -> Self {
         Self {
-            disable_calls: true,
-            disable_decleration: true,
+            policy,
+            fake_clock: None,
+            seed: None,
+            args: vec![],
         }
     }
 }
+
+impl Default for InterpreterOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
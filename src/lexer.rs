@@ -46,6 +46,12 @@ impl<'a> Lexer<'a> {
                         "and" => self.tokens.push_back(Token::Keyword(Keyword::And)),
                         "or" => self.tokens.push_back(Token::Keyword(Keyword::Or)),
                         "none" => self.tokens.push_back(Token::Keyword(Keyword::None)),
+                        "match" => self.tokens.push_back(Token::Keyword(Keyword::Match)),
+                        "if" => self.tokens.push_back(Token::Keyword(Keyword::If)),
+                        "enum" => self.tokens.push_back(Token::Keyword(Keyword::Enum)),
+                        "for" => self.tokens.push_back(Token::Keyword(Keyword::For)),
+                        "in" => self.tokens.push_back(Token::Keyword(Keyword::In)),
+                        "yield" => self.tokens.push_back(Token::Keyword(Keyword::Yield)),
                         _ => self.tokens.push_back(Token::Identifier(word)),
                     }
                 }
@@ -128,12 +134,18 @@ impl<'a> Lexer<'a> {
                     self.tokens.push_back(token)
                 }
                 '=' => {
-                    let token = self.match_char(
-                        peek,
-                        '=',
-                        Token::Operator(Operator::DoubleEqual),
-                        Token::Operator(Operator::Equal),
-                    );
+                    let token = match peek {
+                        '>' => {
+                            self.char_iter.next();
+                            Token::FatArrow
+                        }
+                        _ => self.match_char(
+                            peek,
+                            '=',
+                            Token::Operator(Operator::DoubleEqual),
+                            Token::Operator(Operator::Equal),
+                        ),
+                    };
                     self.tokens.push_back(token)
                 }
                 '!' => {
@@ -146,18 +158,26 @@ impl<'a> Lexer<'a> {
                     self.tokens.push_back(token)
                 }
                 '|' => {
-                    let token = self.match_char(
-                        peek,
-                        '|',
-                        Token::Operator(Operator::DoublePipe),
-                        Token::Operator(Operator::Pipe),
-                    );
+                    let token = match peek {
+                        '>' => {
+                            self.char_iter.next();
+                            Token::Operator(Operator::Pipeline)
+                        }
+                        _ => self.match_char(
+                            peek,
+                            '|',
+                            Token::Operator(Operator::DoublePipe),
+                            Token::Operator(Operator::Pipe),
+                        ),
+                    };
                     self.tokens.push_back(token);
                 }
                 '(' => self.tokens.push_back(Token::LParen),
                 ')' => self.tokens.push_back(Token::RParen),
                 '{' => self.tokens.push_back(Token::LBrace),
                 '}' => self.tokens.push_back(Token::RBrace),
+                '[' => self.tokens.push_back(Token::LBracket),
+                ']' => self.tokens.push_back(Token::RBracket),
                 ',' => self.tokens.push_back(Token::Comma),
                 ';' => self.tokens.push_back(Token::Semicolon),
                 ':' => self.tokens.push_back(Token::Colon),
@@ -189,7 +209,7 @@ impl<'a> Lexer<'a> {
     fn parse_string(&mut self, start: &str) -> String {
         let mut buffer = String::new();
 
-        while let Some(c) = self.char_iter.next() {
+        for c in self.char_iter.by_ref() {
             if c.to_string() == start {
                 break;
             } else {
@@ -382,3 +402,46 @@ fn should_lex_bool_operations() {
         ]
     );
 }
+
+#[test]
+fn should_lex_match() {
+    let mut lexer = Lexer::new("match x { 1 | 2 if y => 3 }");
+    lexer.lex();
+    assert_eq!(
+        lexer.tokens,
+        [
+            Token::Keyword(Keyword::Match),
+            Token::Identifier(String::from("x")),
+            Token::LBrace,
+            Token::Number(1.0),
+            Token::Operator(Operator::Pipe),
+            Token::Number(2.0),
+            Token::Keyword(Keyword::If),
+            Token::Identifier(String::from("y")),
+            Token::FatArrow,
+            Token::Number(3.0),
+            Token::RBrace,
+            Token::EndOfFile,
+        ]
+    );
+}
+
+#[test]
+fn should_lex_pipeline() {
+    let mut lexer = Lexer::new("x |> |y| y || z");
+    lexer.lex();
+    assert_eq!(
+        lexer.tokens,
+        [
+            Token::Identifier(String::from("x")),
+            Token::Operator(Operator::Pipeline),
+            Token::Operator(Operator::Pipe),
+            Token::Identifier(String::from("y")),
+            Token::Operator(Operator::Pipe),
+            Token::Identifier(String::from("y")),
+            Token::Operator(Operator::DoublePipe),
+            Token::Identifier(String::from("z")),
+            Token::EndOfFile,
+        ]
+    );
+}
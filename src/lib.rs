@@ -0,0 +1,39 @@
+//! Neko is a small dynamically typed scripting language.
+//!
+//! The crate exposes the interpreter so Neko can be embedded in Rust
+//! programs; the `neko` binary is a thin REPL/CLI on top of it.
+//!
+//! ```
+//! use neko::{Interpreter, Value};
+//!
+//! let mut interpreter = Interpreter::new();
+//! let result = interpreter.interpret("let x = 20; x * 2 + 2;").unwrap();
+//! assert_eq!(result, Value::Number(42.0));
+//! ```
+
+mod ast;
+mod conversion;
+mod enviroment;
+mod generator;
+mod input;
+mod interpreter;
+mod interpreter_option;
+mod lexer;
+mod misc;
+mod parser;
+mod policy;
+mod semantic_analyzer;
+mod serialize;
+mod stdlib;
+mod symbol;
+mod symbol_table;
+mod token;
+
+pub use conversion::{FromValue, HostFunction, IntoResult, IntoValue};
+pub use enviroment::{Env, FunctionType, NativeFunction, Value};
+pub use input::{InputSource, StdinInput, StringInput};
+pub use interpreter::{colored_output, output, IResult, Interpreter, InterruptHandle};
+pub use interpreter_option::InterpreterOptions;
+pub use misc::{NekoError, NekoWarning};
+pub use policy::{Policy, DEFAULT_MAX_DEPTH, MODULES};
+pub use serialize::{from_value, to_value};
@@ -1,38 +1,51 @@
-use interpreter::{IResult, Interpreter};
+use neko::{IResult, Interpreter, InterpreterOptions, NekoError, Policy};
 use repl::Repl;
 use rustyline::error::ReadlineError;
-use std::{fs, io::Result as IOResult, path::PathBuf};
-use structopt::StructOpt;
+use std::{fs, io::Result as IOResult, path::PathBuf, process, thread, time::Duration};
+use structopt::{clap::AppSettings, StructOpt};
 
-mod ast;
 mod editor_helper;
-mod enviroment;
-mod interpreter;
-mod interpreter_option;
-mod lexer;
-mod misc;
-mod parser;
 mod repl;
-mod semantic_analyzer;
-mod symbol;
-mod symbol_table;
-mod token;
 mod built_info {
     include!(concat!(env!("OUT_DIR"), "/built.rs"));
 }
 
 #[derive(StructOpt, Debug)]
-#[structopt(name = "Neko")]
+#[structopt(name = "Neko", setting = AppSettings::TrailingVarArg)]
 struct CLIArgs {
     /// file to process
     file: Option<PathBuf>,
+    /// arguments passed to the script as process.args
+    #[structopt(allow_hyphen_values = true)]
+    args: Vec<String>,
+    /// allow scripts to use the fs module inside this directory, can be repeated
+    #[structopt(long, parse(from_os_str), number_of_values = 1)]
+    allow_fs: Vec<PathBuf>,
+    /// policy scripts run under, sandboxed limits modules, steps and allocation
+    #[structopt(long, default_value = "trusted", possible_values = &["trusted", "sandboxed"])]
+    profile: String,
+    /// seed for the random module, making runs reproducible
+    #[structopt(long)]
+    seed: Option<u64>,
+    /// cancel the script after this many milliseconds
+    #[structopt(long)]
+    timeout: Option<u64>,
 }
 
-fn log_result(result: IResult) {
+fn log_warnings(interpreter: &Interpreter) {
+    for warning in interpreter.warnings() {
+        eprintln!("{}", warning);
+    }
+}
+
+/// Prints the result, returning the exit code if the script called `process.exit`.
+fn log_result(result: IResult) -> Option<i32> {
     match result {
-        Ok(val) => println!("{}", interpreter::colored_output(&val)),
+        Ok(val) => println!("{}", neko::colored_output(&val)),
+        Err(NekoError::Exit(code)) => return Some(code),
         Err(err) => eprintln!("{}", err),
     }
+    None
 }
 
 const HELP: &str = r#".editor   Enter editor mode
@@ -46,13 +59,40 @@ fn main() -> IOResult<()> {
     let _ = ansi_term::enable_ansi_support();
 
     let args = CLIArgs::from_args();
+    let mut policy = Policy::profile(&args.profile).expect("profile is validated by clap");
+    if !args.allow_fs.is_empty() && !policy.allows_module("fs") {
+        policy.modules.push(String::from("fs"));
+    }
+    policy.fs_roots = args.allow_fs;
+    let mut options = InterpreterOptions::with_policy(policy);
+    options.seed = args.seed;
+    options.args = args.args;
 
     if let Some(file) = args.file {
-        let mut interpreter = Interpreter::new();
-        if let Err(err) = interpreter.interpret(&fs::read_to_string(file)?) {
-            eprintln!("{}", err)
-        };
-        Ok(())
+        let mut interpreter = Interpreter::with_options(options);
+        let source = fs::read_to_string(file)?;
+        if let Some(timeout) = args.timeout {
+            let handle = interpreter.interrupt_handle();
+            // Interrupts raised before the evaluation starts are ignored, so
+            // the deadline keeps being enforced until the process exits.
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(timeout));
+                loop {
+                    handle.interrupt();
+                    thread::sleep(Duration::from_millis(10));
+                }
+            });
+        }
+        let result = interpreter.interpret(&source);
+        log_warnings(&interpreter);
+        match result {
+            Ok(_) => Ok(()),
+            Err(NekoError::Exit(code)) => process::exit(code),
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1)
+            }
+        }
     } else {
         println!(
             "Neko v{} | [{}, {}] for {}.\nType '.help' for more information.",
@@ -64,7 +104,8 @@ fn main() -> IOResult<()> {
             built_info::TARGET,
         );
 
-        let mut repl = Repl::new();
+        let mut repl = Repl::new(options);
+        let mut exit_code = None;
         let _ = repl.editor.load_history("history.txt");
         loop {
             let readline = if repl.editor_enabled {
@@ -103,11 +144,22 @@ fn main() -> IOResult<()> {
                                                     .unwrap()
                                                     .interpreter
                                                     .borrow_mut()
-                                                    .interpret(&&content);
+                                                    .interpret(&content);
+                                                log_warnings(
+                                                    &repl
+                                                        .editor
+                                                        .helper()
+                                                        .unwrap()
+                                                        .interpreter
+                                                        .borrow(),
+                                                );
                                                 if result.is_ok() {
                                                     repl.add_history(&line);
                                                 };
-                                                log_result(result);
+                                                if let Some(code) = log_result(result) {
+                                                    exit_code = Some(code);
+                                                    break;
+                                                }
                                             }
                                             Err(err) => eprintln!("{}", err),
                                         },
@@ -137,10 +189,16 @@ fn main() -> IOResult<()> {
                                         .interpreter
                                         .borrow_mut()
                                         .interpret(&line);
+                                    log_warnings(
+                                        &repl.editor.helper().unwrap().interpreter.borrow(),
+                                    );
                                     if result.is_ok() {
                                         repl.add_history(&line);
                                     };
-                                    log_result(result);
+                                    if let Some(code) = log_result(result) {
+                                        exit_code = Some(code);
+                                        break;
+                                    }
                                 }
                             }
                         }
@@ -161,6 +219,9 @@ fn main() -> IOResult<()> {
             }
         }
         repl.editor.save_history("history.txt").unwrap();
-        Ok(())
+        match exit_code {
+            Some(code) => process::exit(code),
+            None => Ok(()),
+        }
     }
 }
@@ -1,12 +1,21 @@
 use ansi_term::Colour;
 use std::fmt::{self, Display, Formatter};
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum NekoError {
     SyntaxError(String),
     ReferenceError(String),
     TypeError(String),
+    IOError(String),
+    PermissionError(String),
     UnknownError(String),
+    /// A step budget or call depth limit from the options was exceeded.
+    ResourceError(String),
+    /// Evaluation was stopped through an `InterruptHandle`.
+    Cancelled,
+    /// Raised by `process.exit`, unwinds the script with the given exit code.
+    Exit(i32),
 }
 
 impl Display for NekoError {
@@ -23,11 +32,29 @@ impl Display for NekoError {
             NekoError::TypeError(err) => {
                 f.write_str(&format!("[{}]: {}", Colour::Red.paint("Type Error"), err))
             }
+            NekoError::IOError(err) => {
+                f.write_str(&format!("[{}]: {}", Colour::Red.paint("IO Error"), err))
+            }
+            NekoError::PermissionError(err) => f.write_str(&format!(
+                "[{}]: {}",
+                Colour::Red.paint("Permission Error"),
+                err
+            )),
             NekoError::UnknownError(err) => f.write_str(&format!(
                 "[{}]: {}",
                 Colour::Red.paint("Unknown Error"),
                 err
             )),
+            NekoError::ResourceError(err) => f.write_str(&format!(
+                "[{}]: {}",
+                Colour::Red.paint("Resource Error"),
+                err
+            )),
+            NekoError::Cancelled => f.write_str(&format!(
+                "[{}]: Evaluation was cancelled",
+                Colour::Red.paint("Cancelled")
+            )),
+            NekoError::Exit(code) => f.write_str(&format!("Exited with code {}", code)),
         }
     }
 }
@@ -37,3 +64,20 @@ impl From<String> for NekoError {
         NekoError::UnknownError(string)
     }
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NekoWarning {
+    UnreachablePattern(String),
+}
+
+impl Display for NekoWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NekoWarning::UnreachablePattern(warning) => f.write_str(&format!(
+                "[{}]: {}",
+                Colour::Yellow.paint("Unreachable Pattern"),
+                warning
+            )),
+        }
+    }
+}
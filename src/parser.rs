@@ -4,16 +4,45 @@ use crate::{ast::*, lexer::Lexer, misc::NekoError, token::*};
 
 type PResult = Result<Node, NekoError>;
 
+/// How deeply expressions, blocks and patterns may nest, keeping the recursive
+/// parser, and the analyzer and interpreter after it, within the Rust stack.
+const MAX_NESTING: usize = 64;
+
 #[derive(Debug)]
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
+    /// One entry per function body being parsed, set once it contains a `yield`.
+    function_yields: Vec<bool>,
+    nesting: usize,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(text: &'a str) -> Self {
-        let mut lexer = Lexer::new(&text);
+        let mut lexer = Lexer::new(text);
         lexer.lex();
-        Self { lexer }
+        Self {
+            lexer,
+            function_yields: vec![],
+            nesting: 0,
+        }
+    }
+
+    /// Parses one level deeper, failing past `MAX_NESTING` instead of
+    /// overflowing the stack.
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, NekoError>,
+    ) -> Result<T, NekoError> {
+        if self.nesting >= MAX_NESTING {
+            return Err(NekoError::SyntaxError(format!(
+                "Code cannot be nested more than {} levels deep",
+                MAX_NESTING
+            )));
+        }
+        self.nesting += 1;
+        let result = parse(self);
+        self.nesting -= 1;
+        result
     }
 
     /*
@@ -25,16 +54,21 @@ impl<'a> Parser<'a> {
     */
 
     fn key_value_pair(&mut self) -> Result<(String, Node), NekoError> {
-        if let Token::Identifier(iden) = self.lexer.peek() {
-            self.lexer.next();
-            self.eat(Token::Colon)?;
-            let value = self.expression()?;
-            Ok((iden, value))
-        } else {
-            Err(NekoError::SyntaxError(format!(
+        match self.lexer.peek() {
+            Token::Identifier(iden) => {
+                self.lexer.next();
+                self.eat(Token::Colon)?;
+                let value = self.expression()?;
+                Ok((iden, value))
+            }
+            // `{` starts an object, not a block, where a statement is expected.
+            Token::Keyword(Keyword::Yield) => Err(NekoError::SyntaxError(String::from(
+                "'yield' cannot be used inside '{ }' as it is an object, yield from the function body, a for loop or a match arm",
+            ))),
+            token => Err(NekoError::SyntaxError(format!(
                 "Expected identifier got {}",
-                self.lexer.peek()
-            )))
+                token
+            ))),
         }
     }
 
@@ -47,12 +81,13 @@ impl<'a> Parser<'a> {
             Token::String(string) => Ok(Node::String(string)),
             Token::Boolean(boolean) => Ok(Node::Boolean(boolean)),
             Token::Keyword(Keyword::None) => Ok(Node::None),
+            Token::Keyword(Keyword::Match) => self.match_expression(),
             Token::LParen => {
-                let result = self.expression();
+                let result = self.expression()?;
                 let current_token = self.lexer.next();
 
                 match current_token {
-                    Token::RParen => result,
+                    Token::RParen => Ok(result),
                     _ => Err(NekoError::SyntaxError(format!(
                         "Expected closing ')', got {}",
                         current_token
@@ -78,10 +113,184 @@ impl<'a> Parser<'a> {
                 self.eat(Token::RBrace)?;
                 Ok(Node::Object(Box::new(Object { values })))
             }
+            Token::LBracket => {
+                let mut items = vec![];
+
+                loop {
+                    match self.lexer.peek() {
+                        Token::RBracket => break,
+                        Token::Comma => {
+                            self.lexer.next();
+                        }
+                        _ => items.push(self.expression()?),
+                    }
+                }
+
+                self.eat(Token::RBracket)?;
+                Ok(Node::List(items))
+            }
             _ => Err(NekoError::SyntaxError(String::from("Invalid Syntax"))),
         }
     }
 
+    fn pattern(&mut self) -> Result<Pattern, NekoError> {
+        self.nested(|parser| {
+            let mut patterns = vec![parser.single_pattern()?];
+
+            while let Token::Operator(Operator::Pipe) = parser.lexer.peek() {
+                parser.lexer.next();
+                patterns.push(parser.single_pattern()?);
+            }
+
+            if patterns.len() == 1 {
+                Ok(patterns.remove(0))
+            } else {
+                Ok(Pattern::Alternative(patterns))
+            }
+        })
+    }
+
+    fn single_pattern(&mut self) -> Result<Pattern, NekoError> {
+        match self.lexer.next() {
+            Token::Number(num) => Ok(Pattern::Literal(Node::Number(num))),
+            Token::Operator(Operator::Minus) => match self.lexer.next() {
+                Token::Number(num) => Ok(Pattern::Literal(Node::Number(-num))),
+                token => Err(NekoError::SyntaxError(format!(
+                    "Expected number, got {}",
+                    token
+                ))),
+            },
+            Token::String(string) => Ok(Pattern::Literal(Node::String(string))),
+            Token::Boolean(boolean) => Ok(Pattern::Literal(Node::Boolean(boolean))),
+            Token::Keyword(Keyword::None) => Ok(Pattern::Literal(Node::None)),
+            Token::Identifier(iden) if iden == "_" => Ok(Pattern::Wildcard),
+            Token::Identifier(iden) if self.lexer.peek() == Token::Dot => {
+                self.lexer.next();
+                let variant = match self.lexer.next() {
+                    Token::Identifier(variant) => variant,
+                    token => {
+                        return Err(NekoError::SyntaxError(format!(
+                            "Expected identifier, got {}",
+                            token
+                        )))
+                    }
+                };
+                let fields = if let Token::LParen = self.lexer.peek() {
+                    self.lexer.next();
+                    let mut fields = vec![];
+                    loop {
+                        match self.lexer.peek() {
+                            Token::RParen => break,
+                            Token::Comma => {
+                                self.lexer.next();
+                            }
+                            _ => fields.push(self.pattern()?),
+                        }
+                    }
+                    self.eat(Token::RParen)?;
+                    Some(fields)
+                } else {
+                    None
+                };
+                Ok(Pattern::Variant {
+                    enum_name: iden,
+                    variant,
+                    fields,
+                })
+            }
+            Token::Identifier(iden) => Ok(Pattern::Identifier(iden)),
+            Token::LBrace => {
+                let mut fields = vec![];
+
+                loop {
+                    match self.lexer.peek() {
+                        Token::RBrace => break,
+                        Token::Comma => {
+                            self.lexer.next();
+                        }
+                        Token::Identifier(key) => {
+                            self.lexer.next();
+                            let pattern = if let Token::Colon = self.lexer.peek() {
+                                self.lexer.next();
+                                self.pattern()?
+                            } else {
+                                Pattern::Identifier(key.clone())
+                            };
+                            fields.push((key, pattern));
+                        }
+                        token => {
+                            return Err(NekoError::SyntaxError(format!(
+                                "Expected identifier got {}",
+                                token
+                            )))
+                        }
+                    }
+                }
+
+                self.eat(Token::RBrace)?;
+                Ok(Pattern::Object(fields))
+            }
+            token => Err(NekoError::SyntaxError(format!(
+                "Expected pattern, got {}",
+                token
+            ))),
+        }
+    }
+
+    fn match_arm(&mut self) -> Result<MatchArm, NekoError> {
+        let pattern = self.pattern()?;
+        let guard = if let Token::Keyword(Keyword::If) = self.lexer.peek() {
+            self.lexer.next();
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.eat(Token::FatArrow)?;
+        let body = if let Token::LBrace = self.lexer.peek() {
+            self.block()?
+        } else {
+            self.expression()?
+        };
+        Ok(MatchArm {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
+    fn match_expression(&mut self) -> PResult {
+        let subject = self.expression()?;
+        let mut arms = vec![];
+
+        self.eat(Token::LBrace)?;
+        loop {
+            match self.lexer.peek() {
+                Token::RBrace => break,
+                _ => {
+                    let arm = self.match_arm()?;
+                    let is_block = matches!(arm.body, Node::Block(_));
+                    arms.push(arm);
+                    match self.lexer.peek() {
+                        Token::Comma => {
+                            self.lexer.next();
+                        }
+                        Token::RBrace => (),
+                        _ if is_block => (),
+                        token => {
+                            return Err(NekoError::SyntaxError(format!(
+                                "Expected ',' or '}}', got {}",
+                                token
+                            )))
+                        }
+                    }
+                }
+            }
+        }
+        self.eat(Token::RBrace)?;
+
+        Ok(Node::Match(Box::new(Match { subject, arms })))
+    }
+
     fn call_expression(&mut self) -> PResult {
         let mut node = self.value()?;
         loop {
@@ -99,6 +308,15 @@ impl<'a> Parser<'a> {
                         node = Node::Index(Box::new(Index { target: node, key }))
                     };
                 }
+                Token::LBracket => {
+                    self.eat(Token::LBracket)?;
+                    let index = self.expression()?;
+                    self.eat(Token::RBracket)?;
+                    node = Node::Subscript(Box::new(Subscript {
+                        target: node,
+                        index,
+                    }))
+                }
                 _ => break,
             }
         }
@@ -114,7 +332,7 @@ impl<'a> Parser<'a> {
                 self.lexer.next();
                 Node::UnaryOperator(Box::new(UnaryOperator {
                     operator: token,
-                    expression: self.unary_expression()?,
+                    expression: self.nested(Self::unary_expression)?,
                 }))
             }
             _ => self.call_expression()?,
@@ -323,8 +541,59 @@ impl<'a> Parser<'a> {
         Ok(node)
     }
 
+    /// A pipeline stage is called with the piped value, which fills the single
+    /// `_` placeholder in its callee or arguments, at any depth, or else becomes the
+    /// first argument. A stage that is not a call gets it as the only argument.
+    fn pipeline_stage(&mut self, value: Node) -> PResult {
+        let stage = match (self.lexer.peek(), self.lexer.get_index(1)) {
+            // Lambda bodies stop at the next '|>' so that the pipeline can continue.
+            (Token::Operator(Operator::Pipe), Token::Identifier(_))
+            | (Token::Operator(Operator::DoublePipe), _) => {
+                self.lambda_with_body(|parser| match parser.lexer.peek() {
+                    Token::LBrace => parser.block(),
+                    _ => Ok(Node::Block(vec![parser.logical_or()?])),
+                })?
+            }
+            _ => self.logical_or()?,
+        };
+
+        if let Node::FunctionCall(mut call) = stage {
+            let mut placeholders = vec![];
+            call.function.placeholders(&mut placeholders);
+            for argument in &mut call.arguments {
+                argument.placeholders(&mut placeholders);
+            }
+            match placeholders.len() {
+                0 => call.arguments.insert(0, value),
+                1 => *placeholders.remove(0) = value,
+                _ => {
+                    return Err(NekoError::SyntaxError(String::from(
+                        "A pipeline stage can only have one '_' placeholder",
+                    )))
+                }
+            }
+            return Ok(Node::FunctionCall(call));
+        }
+
+        Ok(Node::FunctionCall(Box::new(FunctionCall {
+            function: stage,
+            arguments: vec![value],
+        })))
+    }
+
+    fn pipeline(&mut self) -> PResult {
+        let mut node = self.logical_or()?;
+
+        while let Token::Operator(Operator::Pipeline) = self.lexer.peek() {
+            self.lexer.next();
+            node = self.pipeline_stage(node)?;
+        }
+
+        Ok(node)
+    }
+
     pub fn assignment(&mut self) -> PResult {
-        let expression = self.logical_or()?;
+        let expression = self.pipeline()?;
 
         match self.lexer.peek() {
             Token::Operator(Operator::Equal)
@@ -334,7 +603,7 @@ impl<'a> Parser<'a> {
             | Token::Operator(Operator::DivEqual)
             | Token::Operator(Operator::ExponentEqual)
             | Token::Operator(Operator::ModulusEqual) => {
-                if let Node::Identifier(_) | Node::Index(_) = &expression {
+                if let Node::Identifier(_) | Node::Index(_) | Node::Subscript(_) = &expression {
                     let operator = self.lexer.next();
                     let mut value = self.expression()?;
                     value = match operator {
@@ -396,6 +665,13 @@ impl<'a> Parser<'a> {
                                 value,
                             })))
                         }
+                        Node::Subscript(subscript) => {
+                            Ok(Node::SetSubscriptExpr(Box::new(SetSubscriptExpr {
+                                target: subscript.target,
+                                index: subscript.index,
+                                value,
+                            })))
+                        }
                         node => Err(NekoError::TypeError(format!("Invalid assignment {}", node))),
                     }
                 } else {
@@ -410,36 +686,50 @@ impl<'a> Parser<'a> {
     }
 
     fn lambda(&mut self) -> PResult {
+        self.lambda_with_body(Self::lambda_block)
+    }
+
+    fn lambda_with_body(&mut self, body: fn(&mut Self) -> PResult) -> PResult {
         let params = if let Token::Operator(Operator::Pipe) = self.lexer.peek() {
             self.lambda_parameters()?
         } else {
             self.eat(Token::Operator(Operator::DoublePipe))?;
             vec![]
         };
+        self.function_yields.push(false);
+        let block = body(self);
+        let generator = self.function_yields.pop().unwrap_or(false);
         Ok(Node::Lambda(Box::new(Lambda {
             id: format!("{:p}", &params),
             params,
-            block: self.lambda_block()?,
+            block: block?,
+            generator,
         })))
     }
 
     fn expression(&mut self) -> PResult {
-        match self.lexer.peek() {
+        self.nested(|parser| match parser.lexer.peek() {
             Token::Operator(Operator::Pipe) => {
-                if let Token::Identifier(_) = self.lexer.get_index(1) {
-                    self.lambda()
+                if let Token::Identifier(_) = parser.lexer.get_index(1) {
+                    parser.lambda()
                 } else {
                     Err(NekoError::SyntaxError(String::from("Invalid Syntax")))
                 }
             }
-            Token::Operator(Operator::DoublePipe) => self.lambda(),
-            _ => self.assignment(),
-        }
+            Token::Operator(Operator::DoublePipe) => parser.lambda(),
+            _ => parser.assignment(),
+        })
     }
 
     fn expression_statment(&mut self) -> PResult {
         let expr = self.expression()?;
-        self.eat(Token::Semicolon)?;
+        match (&expr, self.lexer.peek()) {
+            // Like function bodies, a `match` ending in '}' needs no semicolon.
+            (Node::Match(_), token) if token != Token::Semicolon => (),
+            _ => {
+                self.eat(Token::Semicolon)?;
+            }
+        }
         Ok(Node::Expression(Box::new(expr)))
     }
 
@@ -475,13 +765,57 @@ impl<'a> Parser<'a> {
     }
 
     fn decerlation(&mut self) -> PResult {
-        let token = self.lexer.peek();
+        self.nested(|parser| match parser.lexer.peek() {
+            Token::Keyword(Keyword::Let) => parser.variable_decleration(),
+            Token::Keyword(Keyword::Function) => parser.function_decleration(),
+            Token::Keyword(Keyword::Enum) => parser.enum_decleration(),
+            Token::Keyword(Keyword::For) => parser.for_statement(),
+            Token::Keyword(Keyword::Yield) => parser.yield_statement(),
+            _ => parser.expression_statment(),
+        })
+    }
 
-        match token {
-            Token::Keyword(Keyword::Let) => self.variable_decleration(),
-            Token::Keyword(Keyword::Function) => self.function_decleration(),
-            _ => self.expression_statment(),
+    fn for_statement(&mut self) -> PResult {
+        self.eat(Token::Keyword(Keyword::For))?;
+
+        let variable = match self.lexer.next() {
+            Token::Identifier(identifier) => identifier,
+            token => {
+                return Err(NekoError::SyntaxError(format!(
+                    "Expected identifier, got {}",
+                    token
+                )))
+            }
+        };
+        self.eat(Token::Keyword(Keyword::In))?;
+        let iterable = self.expression()?;
+        let block = self.block()?;
+
+        Ok(Node::ForIn(Box::new(ForIn {
+            variable,
+            iterable,
+            block,
+        })))
+    }
+
+    fn yield_statement(&mut self) -> PResult {
+        self.eat(Token::Keyword(Keyword::Yield))?;
+
+        match self.function_yields.last_mut() {
+            Some(yields) => *yields = true,
+            None => {
+                return Err(NekoError::SyntaxError(String::from(
+                    "'yield' outside of a function",
+                )))
+            }
         }
+
+        let value = match self.lexer.peek() {
+            Token::Semicolon => Node::None,
+            _ => self.expression()?,
+        };
+        self.eat(Token::Semicolon)?;
+        Ok(Node::Yield(Box::new(value)))
     }
 
     fn function_decleration(&mut self) -> PResult {
@@ -491,11 +825,14 @@ impl<'a> Parser<'a> {
             Token::Identifier(identifier) => {
                 self.lexer.next();
                 let param_list = self.parameter_list()?;
-                let block_node = self.block()?;
+                self.function_yields.push(false);
+                let block_node = self.block();
+                let generator = self.function_yields.pop().unwrap_or(false);
                 Ok(Node::FunctionDecleration(Box::new(FunctionDecleration {
                     name: identifier,
                     params: param_list,
-                    block: block_node,
+                    block: block_node?,
+                    generator,
                 })))
             }
             token => Err(NekoError::SyntaxError(format!(
@@ -505,6 +842,51 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn enum_decleration(&mut self) -> PResult {
+        self.eat(Token::Keyword(Keyword::Enum))?;
+
+        let name = match self.lexer.next() {
+            Token::Identifier(identifier) => identifier,
+            token => {
+                return Err(NekoError::SyntaxError(format!(
+                    "Expected identifier, got {}",
+                    token
+                )))
+            }
+        };
+
+        let mut variants = vec![];
+        self.eat(Token::LBrace)?;
+        loop {
+            match self.lexer.next() {
+                Token::RBrace => break,
+                Token::Comma => (),
+                Token::Identifier(variant) => {
+                    let fields = if let Token::LParen = self.lexer.peek() {
+                        self.parameter_list()?
+                    } else {
+                        vec![]
+                    };
+                    variants.push(EnumVariant {
+                        name: variant,
+                        fields,
+                    })
+                }
+                token => {
+                    return Err(NekoError::SyntaxError(format!(
+                        "Expected variant or '}}', got {}",
+                        token
+                    )))
+                }
+            }
+        }
+
+        Ok(Node::EnumDecleration(Box::new(EnumDecleration {
+            name,
+            variants,
+        })))
+    }
+
     fn lambda_block(&mut self) -> PResult {
         if let Token::LBrace = self.lexer.peek() {
             self.block()
@@ -756,8 +1138,198 @@ fn should_parse_function_statement() {
                             right: Node::Identifier(String::from("baz"))
                         })))
                     }
-                ))])
+                ))]),
+                generator: false,
+            }
+        ))])
+    );
+}
+
+#[test]
+fn should_parse_match_expression() {
+    let mut parser = Parser::new(
+        "match shape { 0 | -1 => 'none', { kind: 'circle', r } if r > 1 => { r; } _ => 'other' }",
+    );
+    let result = parser.parse().unwrap();
+    assert_eq!(
+        result,
+        Node::Compound(vec![Node::Expression(Box::new(Node::Match(Box::new(
+            Match {
+                subject: Node::Identifier(String::from("shape")),
+                arms: vec![
+                    MatchArm {
+                        pattern: Pattern::Alternative(vec![
+                            Pattern::Literal(Node::Number(0.0)),
+                            Pattern::Literal(Node::Number(-1.0)),
+                        ]),
+                        guard: None,
+                        body: Node::String(String::from("none")),
+                    },
+                    MatchArm {
+                        pattern: Pattern::Object(vec![
+                            (
+                                String::from("kind"),
+                                Pattern::Literal(Node::String(String::from("circle")))
+                            ),
+                            (String::from("r"), Pattern::Identifier(String::from("r"))),
+                        ]),
+                        guard: Some(Node::BinOperator(Box::new(BinOperator {
+                            left: Node::Identifier(String::from("r")),
+                            operator: Token::Operator(Operator::GreaterThan),
+                            right: Node::Number(1.0),
+                        }))),
+                        body: Node::Block(vec![Node::Expression(Box::new(Node::Identifier(
+                            String::from("r")
+                        )))]),
+                    },
+                    MatchArm {
+                        pattern: Pattern::Wildcard,
+                        guard: None,
+                        body: Node::String(String::from("other")),
+                    },
+                ],
+            }
+        ))))])
+    );
+}
+
+#[test]
+fn should_parse_enum_decleration() {
+    let mut parser = Parser::new("enum Shape { Circle(r), Rect(w, h), Empty }");
+    let result = parser.parse().unwrap();
+    assert_eq!(
+        result,
+        Node::Compound(vec![Node::EnumDecleration(Box::new(EnumDecleration {
+            name: String::from("Shape"),
+            variants: vec![
+                EnumVariant {
+                    name: String::from("Circle"),
+                    fields: vec![String::from("r")],
+                },
+                EnumVariant {
+                    name: String::from("Rect"),
+                    fields: vec![String::from("w"), String::from("h")],
+                },
+                EnumVariant {
+                    name: String::from("Empty"),
+                    fields: vec![],
+                },
+            ],
+        }))])
+    );
+}
+
+#[test]
+fn should_parse_generator_function() {
+    let mut parser = Parser::new("function count(n) { for i in range(n) { yield i; } }");
+    let result = parser.parse().unwrap();
+    assert_eq!(
+        result,
+        Node::Compound(vec![Node::FunctionDecleration(Box::new(
+            FunctionDecleration {
+                name: String::from("count"),
+                params: vec![String::from("n")],
+                block: Node::Block(vec![Node::ForIn(Box::new(ForIn {
+                    variable: String::from("i"),
+                    iterable: Node::FunctionCall(Box::new(FunctionCall {
+                        function: Node::Identifier(String::from("range")),
+                        arguments: vec![Node::Identifier(String::from("n"))],
+                    })),
+                    block: Node::Block(vec![Node::Yield(Box::new(Node::Identifier(
+                        String::from("i")
+                    )))]),
+                }))]),
+                generator: true,
             }
         ))])
     );
 }
+
+#[test]
+fn shouldnt_parse_yield_outside_function() {
+    let mut parser = Parser::new("yield 1;");
+    assert_eq!(
+        parser.parse(),
+        Err(NekoError::SyntaxError(String::from(
+            "'yield' outside of a function"
+        )))
+    );
+}
+
+#[test]
+fn should_parse_pipeline() {
+    let mut parser = Parser::new("x |> f |> g(1, _) |> h(2);");
+    let result = parser.parse().unwrap();
+    let call = |function, arguments| {
+        Node::FunctionCall(Box::new(FunctionCall {
+            function,
+            arguments,
+        }))
+    };
+    let iden = |name: &str| Node::Identifier(String::from(name));
+    assert_eq!(
+        result,
+        Node::Compound(vec![Node::Expression(Box::new(call(
+            iden("h"),
+            vec![
+                call(
+                    iden("g"),
+                    vec![Node::Number(1.0), call(iden("f"), vec![iden("x")])]
+                ),
+                Node::Number(2.0)
+            ]
+        )))])
+    );
+
+    // The lambda's body stops at the next '|>' instead of swallowing the rest.
+    let mut parser = Parser::new("x |> |y| y * 2 |> f;");
+    let result = parser.parse().unwrap();
+    let is_piped_into_f = match &result {
+        Node::Compound(nodes) => match &nodes[..] {
+            [Node::Expression(expr)] => match &**expr {
+                Node::FunctionCall(call) => {
+                    call.function == iden("f")
+                        && matches!(&call.arguments[..], [Node::FunctionCall(_)])
+                }
+                _ => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    };
+    assert!(is_piped_into_f, "{:?}", result);
+}
+
+#[test]
+fn shouldnt_parse_deeply_nested_code() {
+    let nested =
+        |open: &str, close: &str, depth| format!("{}1{};", open.repeat(depth), close.repeat(depth));
+    assert!(Parser::new(&nested("(", ")", 60)).parse().is_ok());
+    for text in &[
+        nested("(", ")", 20000),
+        nested("[", "]", 20000),
+        nested("-", "", 20000),
+        nested("for x in y { ", "}", 20000),
+    ] {
+        assert_eq!(
+            Parser::new(text).parse(),
+            Err(NekoError::SyntaxError(String::from(
+                "Code cannot be nested more than 64 levels deep"
+            )))
+        );
+    }
+}
+
+#[test]
+fn shouldnt_parse_pipeline_with_multiple_placeholders() {
+    for text in &["x |> f(_, _);", "x |> f(g(_), _);"] {
+        assert_eq!(
+            Parser::new(text).parse(),
+            Err(NekoError::SyntaxError(String::from(
+                "A pipeline stage can only have one '_' placeholder"
+            ))),
+            "{}",
+            text
+        );
+    }
+}
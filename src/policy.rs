@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+/// Built-in modules a policy can allow, `io` stands for `input`, `read_line`,
+/// `read_all` and `lines`.
+pub const MODULES: [&str; 8] = [
+    "math", "json", "fs", "time", "random", "regex", "process", "io",
+];
+
+/// Call depth allowed to sandboxed code. An 8 MiB main thread stack fits about
+/// 2000 Neko calls in release builds and 300 in debug builds, so runaway
+/// recursion fails with a ResourceError before it can overflow the stack.
+pub const DEFAULT_MAX_DEPTH: usize = 200;
+
+/// Capabilities of evaluated code, enforced by the interpreter while running.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    /// Built-in modules scripts may use, out of `MODULES`. Other modules are
+    /// hidden, including copies of them and their functions made earlier.
+    pub modules: Vec<String>,
+    /// Directories the `fs` module is confined to, relative paths resolve
+    /// against the first one. No directories disables the file system.
+    pub fs_roots: Vec<PathBuf>,
+    /// Whether scripts may reach the network, no built-in does so yet.
+    pub network: bool,
+    /// Whether functions may be called.
+    pub calls: bool,
+    /// Whether variables, functions and enums may be declared.
+    pub declarations: bool,
+    /// Whether scripts may assign to variables, or change objects and lists,
+    /// that existed before the evaluation started, however they are reached.
+    /// Objects and lists made by host functions count as existing ones unless
+    /// they pass them through `Interpreter::created`.
+    pub mutation: bool,
+    /// Steps a single evaluation may take before failing, unlimited when `None`.
+    pub fuel: Option<u64>,
+    /// How deeply Neko functions may call each other, unlimited when `None`,
+    /// in which case deep enough recursion overflows the stack and aborts.
+    /// Nesting in the source is limited by the parser instead.
+    pub max_depth: Option<usize>,
+    /// Budget of approximate bytes that the strings, lists and objects created
+    /// by a single evaluation may add up to, unlimited when `None`. Dropped
+    /// values are not credited back, so a loop that keeps replacing a small
+    /// string still uses it up. This is not a cap on memory held at once,
+    /// which is not tracked.
+    pub max_allocation: Option<usize>,
+}
+
+impl Policy {
+    /// Everything but the file system and the network, the default.
+    pub fn trusted() -> Self {
+        Self {
+            modules: MODULES.iter().map(|module| String::from(*module)).collect(),
+            fs_roots: vec![],
+            network: false,
+            calls: true,
+            declarations: true,
+            mutation: true,
+            fuel: None,
+            max_depth: None,
+            max_allocation: None,
+        }
+    }
+
+    /// For untrusted code: only modules without side effects, and limits on
+    /// steps, call depth and allocation.
+    pub fn sandboxed() -> Self {
+        Self {
+            modules: vec![
+                String::from("math"),
+                String::from("json"),
+                String::from("regex"),
+            ],
+            fuel: Some(10_000_000),
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_allocation: Some(256 << 20),
+            ..Self::trusted()
+        }
+    }
+
+    /// Evaluation that cannot change anything, used for REPL hints.
+    pub fn inert() -> Self {
+        Self {
+            calls: false,
+            declarations: false,
+            mutation: false,
+            fuel: Some(100_000),
+            ..Self::sandboxed()
+        }
+    }
+
+    /// Looks up a policy by the name used for the CLI's `--profile`.
+    pub fn profile(name: &str) -> Option<Self> {
+        match name {
+            "trusted" => Some(Self::trusted()),
+            "sandboxed" => Some(Self::sandboxed()),
+            "inert" => Some(Self::inert()),
+            _ => None,
+        }
+    }
+
+    pub fn allows_module(&self, module: &str) -> bool {
+        self.modules.iter().any(|allowed| allowed == module)
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self::trusted()
+    }
+}
@@ -1,11 +1,36 @@
 use std::cell::RefCell;
 
-use crate::{editor_helper::EditorHelper, interpreter::Interpreter};
+use crate::editor_helper::EditorHelper;
+use neko::{InputSource, Interpreter, InterpreterOptions};
 use rustyline::{
-    completion::FilenameCompleter, config::OutputStreamType, highlight::MatchingBracketHighlighter,
-    hint::HistoryHinter, validate::MatchingBracketValidator, Cmd, CompletionType, Config, Editor,
-    EventHandler, KeyEvent,
+    completion::FilenameCompleter, config::OutputStreamType, error::ReadlineError,
+    highlight::MatchingBracketHighlighter, hint::HistoryHinter, validate::MatchingBracketValidator,
+    Cmd, CompletionType, Config, Editor, EventHandler, KeyEvent,
 };
+use std::io;
+
+/// Reads `input()` calls made from the REPL through rustyline, so prompts
+/// are editable like the REPL's own and Ctrl+D ends the input.
+pub struct ReplInput {
+    editor: Editor<()>,
+}
+
+impl std::fmt::Debug for ReplInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ReplInput")
+    }
+}
+
+impl InputSource for ReplInput {
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        match self.editor.readline(prompt) {
+            Ok(line) => Ok(Some(line)),
+            Err(ReadlineError::Eof) => Ok(None),
+            Err(ReadlineError::Io(err)) => Err(err),
+            Err(err) => Err(io::Error::new(io::ErrorKind::Interrupted, err.to_string())),
+        }
+    }
+}
 
 pub struct Repl {
     pub editor_enabled: bool,
@@ -14,7 +39,7 @@ pub struct Repl {
 }
 
 impl Repl {
-    pub fn new() -> Self {
+    pub fn new(options: InterpreterOptions) -> Self {
         let config = Config::builder()
             .history_ignore_space(true)
             .completion_type(CompletionType::Circular)
@@ -25,8 +50,14 @@ impl Repl {
             highlighter: MatchingBracketHighlighter::new(),
             hinter: HistoryHinter {},
             validator: MatchingBracketValidator::new(),
-            interpreter: RefCell::new(Interpreter::new()),
+            interpreter: RefCell::new(Interpreter::with_options(options)),
         };
+        helper
+            .interpreter
+            .borrow_mut()
+            .set_input(Box::new(ReplInput {
+                editor: Editor::new(),
+            }));
 
         let mut editor = Editor::with_config(config);
         editor.set_helper(Some(helper));
@@ -53,9 +84,6 @@ impl Repl {
         }
     }
 
-    pub fn enable_editor(&mut self) {
-        self.editor_enabled = true;
-    }
     pub fn disable_editor(&mut self) {
         self.editor_enabled = false;
     }
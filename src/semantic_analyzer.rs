@@ -1,7 +1,10 @@
 use std::{cell::RefCell, rc::Rc};
 
 use crate::{
-    ast::*, interpreter_option::InterpreterOptions, misc::NekoError, symbol::*,
+    ast::*,
+    interpreter_option::InterpreterOptions,
+    misc::{NekoError, NekoWarning},
+    symbol::*,
     symbol_table::SymbolTable,
 };
 
@@ -10,33 +13,117 @@ type SResult = Result<(), NekoError>;
 #[derive(Debug)]
 pub struct SemanticAnalyzer {
     pub scope: Rc<RefCell<SymbolTable>>,
+    pub warnings: Vec<NekoWarning>,
     interpreter_options: InterpreterOptions,
 }
 
+fn pattern_bindings(pattern: &Pattern, bindings: &mut Vec<String>) {
+    match pattern {
+        Pattern::Identifier(iden) => bindings.push(iden.clone()),
+        Pattern::Alternative(patterns) => {
+            for pattern in patterns {
+                pattern_bindings(pattern, bindings)
+            }
+        }
+        Pattern::Object(fields) => {
+            for (_, pattern) in fields {
+                pattern_bindings(pattern, bindings)
+            }
+        }
+        Pattern::Variant { fields, .. } => {
+            for pattern in fields.iter().flatten() {
+                pattern_bindings(pattern, bindings)
+            }
+        }
+        Pattern::Literal(_) | Pattern::Wildcard => (),
+    }
+}
+
+fn is_irrefutable(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Identifier(_) | Pattern::Wildcard => true,
+        Pattern::Alternative(patterns) => patterns.iter().any(is_irrefutable),
+        Pattern::Literal(_) | Pattern::Object(_) | Pattern::Variant { .. } => false,
+    }
+}
+
+/// Keys of the literals and variants a pattern is limited to, `None` if it can match anything else.
+fn pattern_keys(pattern: &Pattern) -> Option<Vec<String>> {
+    match pattern {
+        Pattern::Literal(node) => Some(vec![format!("{:?}", node)]),
+        Pattern::Variant {
+            enum_name, variant, ..
+        } => Some(vec![format!("{}.{}", enum_name, variant)]),
+        Pattern::Alternative(patterns) => {
+            let mut keys = vec![];
+            for pattern in patterns {
+                keys.extend(pattern_keys(pattern)?);
+            }
+            Some(keys)
+        }
+        _ => None,
+    }
+}
+
+/// Keys of the literals and variants a pattern matches in full.
+fn covered_keys(pattern: &Pattern) -> Vec<String> {
+    match pattern {
+        Pattern::Literal(node) => vec![format!("{:?}", node)],
+        Pattern::Variant {
+            enum_name,
+            variant,
+            fields,
+        } if fields.iter().flatten().all(is_irrefutable) => {
+            vec![format!("{}.{}", enum_name, variant)]
+        }
+        Pattern::Alternative(patterns) => patterns.iter().flat_map(covered_keys).collect(),
+        _ => vec![],
+    }
+}
+
 impl SemanticAnalyzer {
     pub fn new() -> Self {
-        let scope = Rc::new(RefCell::new(SymbolTable::new("global", 1, None)));
+        let scope = Rc::new(RefCell::new(SymbolTable::new(1, None)));
         let built_in = vec![
             Symbol::BuiltInSymbol(String::from("print")),
             Symbol::BuiltInSymbol(String::from("error")),
+            Symbol::BuiltInSymbol(String::from("range")),
+            Symbol::BuiltInSymbol(String::from("list")),
+            Symbol::BuiltInSymbol(String::from("math")),
+            Symbol::BuiltInSymbol(String::from("json")),
+            Symbol::BuiltInSymbol(String::from("fs")),
+            Symbol::BuiltInSymbol(String::from("time")),
+            Symbol::BuiltInSymbol(String::from("random")),
+            Symbol::BuiltInSymbol(String::from("regex")),
+            Symbol::BuiltInSymbol(String::from("process")),
+            Symbol::BuiltInSymbol(String::from("type_of")),
+            Symbol::BuiltInSymbol(String::from("str")),
+            Symbol::BuiltInSymbol(String::from("num")),
+            Symbol::BuiltInSymbol(String::from("bool")),
+            Symbol::BuiltInSymbol(String::from("repr")),
+            Symbol::BuiltInSymbol(String::from("input")),
+            Symbol::BuiltInSymbol(String::from("read_line")),
+            Symbol::BuiltInSymbol(String::from("read_all")),
+            Symbol::BuiltInSymbol(String::from("lines")),
         ];
 
         for built in built_in {
             match built {
-                Symbol::BuiltInSymbol(ref name) => scope.borrow_mut().insert(&name, built.clone()),
+                Symbol::BuiltInSymbol(ref name) => scope.borrow_mut().insert(name, built.clone()),
                 _ => unreachable!(),
             }
         }
 
         Self {
             scope,
+            warnings: vec![],
             interpreter_options: InterpreterOptions::new(),
         }
     }
 
     fn visit_compound(&mut self, nodes: &[Node]) -> SResult {
         for node in nodes {
-            self.visit(&node)?
+            self.visit(node)?
         }
 
         Ok(())
@@ -44,7 +131,7 @@ impl SemanticAnalyzer {
 
     fn visit_block(&mut self, nodes: &[Node]) -> SResult {
         for node in nodes {
-            self.visit(&node)?
+            self.visit(node)?
         }
 
         Ok(())
@@ -68,7 +155,7 @@ impl SemanticAnalyzer {
     }
 
     fn visit_variable_decleration(&mut self, node: &VariabeDecleration) -> SResult {
-        if !self.interpreter_options.disable_decleration {
+        if self.interpreter_options.policy.declarations {
             if self
                 .scope
                 .borrow()
@@ -80,14 +167,15 @@ impl SemanticAnalyzer {
                     &node.identifier
                 )))
             } else {
-                self.scope.borrow_mut().insert(
-                    &node.identifier,
-                    Symbol::VarSymbol(VarSymbol {
-                        name: node.identifier.clone(),
-                        symbol_type: TypeSymbol::Unknown,
+                self.scope
+                    .borrow_mut()
+                    .insert(&node.identifier, Symbol::VarSymbol);
+                match &node.value {
+                    Some(value) => self.visit(value).inspect_err(|_| {
+                        self.scope.borrow_mut().remove(&node.identifier);
                     }),
-                );
-                Ok(())
+                    None => Ok(()),
+                }
             }
         } else {
             Ok(())
@@ -105,33 +193,174 @@ impl SemanticAnalyzer {
         Ok(())
     }
 
-    fn visit_function_call(&mut self, _node: &FunctionCall) -> SResult {
-        /*
-        if let Node::Identifier(identifier) = &node.function {
-            if let Some(symbol) = self.scope.borrow().look_up(identifier, false) {
-                if let Symbol::FunctionSymbol(symbol) = symbol {
-                    if symbol.param.len() != node.arguments.len() {
-                        Err(format!(
-                            "Expected {} number of arguments got {}",
-                            symbol.param.len(),
+    fn visit_function_call(&mut self, node: &FunctionCall) -> SResult {
+        // The callee and arguments are left unchecked, like the targets of
+        // properties and subscripts, so functions can use ones declared later.
+        if let Node::Index(index) = &node.function {
+            if let Node::Identifier(enum_name) = &index.target {
+                if let Some(Symbol::EnumSymbol(_)) = self.scope.borrow().look_up(enum_name, false) {
+                    let variant = self.look_up_variant(enum_name, &index.key)?;
+                    if variant.fields.len() != node.arguments.len() {
+                        return Err(NekoError::TypeError(format!(
+                            "{}.{} expects {} arguments, got {}",
+                            enum_name,
+                            variant.name,
+                            variant.fields.len(),
                             node.arguments.len()
-                        ))
-                    } else {
-                        Ok(())
+                        )));
                     }
-                } else {
-                    Err(format!("Attempt to call non-function {:?}", symbol))
                 }
-            } else {
-                Err(format!("Attempt to call undefined function {}", identifier))
             }
-        } else {
-            Ok(())
         }
-        */
         Ok(())
     }
 
+    fn visit_index(&mut self, node: &Index) -> SResult {
+        if let Node::Identifier(enum_name) = &node.target {
+            if let Some(Symbol::EnumSymbol(_)) = self.scope.borrow().look_up(enum_name, false) {
+                self.look_up_variant(enum_name, &node.key)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn look_up_variant(&self, enum_name: &str, variant: &str) -> Result<EnumVariant, NekoError> {
+        match self.scope.borrow().look_up(enum_name, false) {
+            Some(Symbol::EnumSymbol(symbol)) => symbol.variant(variant).cloned().ok_or_else(|| {
+                NekoError::ReferenceError(format!(
+                    "Enum {} has no variant '{}'",
+                    enum_name, variant
+                ))
+            }),
+            Some(_) => Err(NekoError::TypeError(format!(
+                "{} is not an enum",
+                enum_name
+            ))),
+            None => Err(NekoError::ReferenceError(format!(
+                "{} is not defined",
+                enum_name
+            ))),
+        }
+    }
+
+    fn check_pattern(&self, pattern: &Pattern) -> SResult {
+        match pattern {
+            Pattern::Alternative(patterns) => {
+                for pattern in patterns {
+                    self.check_pattern(pattern)?
+                }
+                Ok(())
+            }
+            Pattern::Object(fields) => {
+                for (_, pattern) in fields {
+                    self.check_pattern(pattern)?
+                }
+                Ok(())
+            }
+            Pattern::Variant {
+                enum_name,
+                variant,
+                fields,
+            } => {
+                let declared = self.look_up_variant(enum_name, variant)?;
+                if let Some(fields) = fields {
+                    if fields.len() != declared.fields.len() {
+                        return Err(NekoError::TypeError(format!(
+                            "{}.{} has {} fields, pattern has {}",
+                            enum_name,
+                            variant,
+                            declared.fields.len(),
+                            fields.len()
+                        )));
+                    }
+                    for pattern in fields {
+                        self.check_pattern(pattern)?
+                    }
+                }
+                Ok(())
+            }
+            Pattern::Literal(_) | Pattern::Identifier(_) | Pattern::Wildcard => Ok(()),
+        }
+    }
+
+    fn visit_match(&mut self, node: &Match) -> SResult {
+        self.visit(&node.subject)?;
+
+        let mut catch_all = false;
+        let mut covered: Vec<String> = vec![];
+        for arm in &node.arms {
+            self.check_pattern(&arm.pattern)?;
+
+            let unreachable = catch_all
+                || pattern_keys(&arm.pattern)
+                    .is_some_and(|keys| keys.iter().all(|key| covered.contains(key)));
+            if unreachable {
+                self.warnings.push(NekoWarning::UnreachablePattern(format!(
+                    "Arm '{}' will never be matched, previous arms cover it",
+                    arm.pattern
+                )));
+            }
+            if arm.guard.is_none() {
+                catch_all = catch_all || is_irrefutable(&arm.pattern);
+                covered.extend(covered_keys(&arm.pattern));
+            }
+
+            let level = self.scope.borrow().scope_level + 1;
+            self.scope = Rc::new(RefCell::new(SymbolTable::new(
+                level,
+                Some(Rc::clone(&self.scope)),
+            )));
+
+            let mut bindings = vec![];
+            pattern_bindings(&arm.pattern, &mut bindings);
+            for binding in bindings {
+                self.scope.borrow_mut().insert(&binding, Symbol::VarSymbol);
+            }
+
+            let result = match &arm.guard {
+                Some(guard) => self.visit(guard).and_then(|_| self.visit(&arm.body)),
+                None => self.visit(&arm.body),
+            };
+
+            self.scope = Rc::clone(
+                Rc::clone(&self.scope)
+                    .borrow()
+                    .enclosing_scope
+                    .as_ref()
+                    .unwrap(),
+            );
+
+            result?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_for_in(&mut self, node: &ForIn) -> SResult {
+        self.visit(&node.iterable)?;
+
+        let level = self.scope.borrow().scope_level + 1;
+        self.scope = Rc::new(RefCell::new(SymbolTable::new(
+            level,
+            Some(Rc::clone(&self.scope)),
+        )));
+        self.scope
+            .borrow_mut()
+            .insert(&node.variable, Symbol::VarSymbol);
+
+        let result = self.visit(&node.block);
+
+        self.scope = Rc::clone(
+            Rc::clone(&self.scope)
+                .borrow()
+                .enclosing_scope
+                .as_ref()
+                .unwrap(),
+        );
+
+        result
+    }
+
     fn visit_expression(&mut self, node: &Node) -> SResult {
         match node {
             Node::BinOperator(node) => self.visit_bin_operator(node),
@@ -151,37 +380,35 @@ impl SemanticAnalyzer {
             Node::SetPropertyExpr(_) => Ok(()),
             Node::FunctionCall(node) => self.visit_function_call(node),
             Node::Lambda(lambda) => self.visit_lambda(lambda),
-            Node::Index(_) => Ok(()),
+            Node::Index(node) => self.visit_index(node),
+            Node::Match(node) => self.visit_match(node),
+            Node::List(items) => {
+                for item in items {
+                    self.visit(item)?
+                }
+                Ok(())
+            }
+            Node::Subscript(_) => Ok(()),
+            Node::SetSubscriptExpr(_) => Ok(()),
             _ => Err(NekoError::SyntaxError(String::from("Invalid Syntax"))),
         }
     }
 
     fn visit_function_decleration(&mut self, node: &FunctionDecleration) -> SResult {
-        if !self.interpreter_options.disable_decleration {
+        if self.interpreter_options.policy.declarations {
             let function_name = &node.name;
             if self.scope.borrow().look_up(function_name, true).is_none() {
-                self.scope.borrow_mut().insert(
-                    &function_name,
-                    Symbol::FunctionSymbol(FunctionSymbol {
-                        name: function_name.clone(),
-                        param: node.params.clone(),
-                    }),
-                );
+                self.scope
+                    .borrow_mut()
+                    .insert(function_name, Symbol::FunctionSymbol);
                 let level = self.scope.borrow().scope_level + 1;
                 self.scope = Rc::new(RefCell::new(SymbolTable::new(
-                    &function_name,
                     level,
                     Some(Rc::clone(&self.scope)),
                 )));
 
                 for param in &node.params {
-                    self.scope.borrow_mut().insert(
-                        &param,
-                        Symbol::VarSymbol(VarSymbol {
-                            name: param.to_string(),
-                            symbol_type: TypeSymbol::Unknown,
-                        }),
-                    );
+                    self.scope.borrow_mut().insert(param, Symbol::VarSymbol);
                 }
 
                 self.visit(&node.block)?;
@@ -206,30 +433,46 @@ impl SemanticAnalyzer {
         }
     }
 
+    fn visit_enum_decleration(&mut self, node: &EnumDecleration) -> SResult {
+        if self.interpreter_options.policy.declarations {
+            if self.scope.borrow().look_up(&node.name, true).is_some() {
+                return Err(NekoError::SyntaxError(format!(
+                    "Duplicate variable {}",
+                    node.name
+                )));
+            }
+            for (index, variant) in node.variants.iter().enumerate() {
+                if node.variants[..index]
+                    .iter()
+                    .any(|other| other.name == variant.name)
+                {
+                    return Err(NekoError::SyntaxError(format!(
+                        "Duplicate variant {}.{}",
+                        node.name, variant.name
+                    )));
+                }
+            }
+            self.scope.borrow_mut().insert(
+                &node.name,
+                Symbol::EnumSymbol(EnumSymbol {
+                    variants: node.variants.clone(),
+                }),
+            );
+        }
+        Ok(())
+    }
+
     fn visit_lambda(&mut self, node: &Lambda) -> SResult {
         let id = &node.id;
-        self.scope.borrow_mut().insert(
-            &id,
-            Symbol::FunctionSymbol(FunctionSymbol {
-                name: id.clone(),
-                param: node.params.clone(),
-            }),
-        );
+        self.scope.borrow_mut().insert(id, Symbol::FunctionSymbol);
         let level = self.scope.borrow().scope_level + 1;
         self.scope = Rc::new(RefCell::new(SymbolTable::new(
-            &id,
             level,
             Some(Rc::clone(&self.scope)),
         )));
 
         for param in &node.params {
-            self.scope.borrow_mut().insert(
-                &param,
-                Symbol::VarSymbol(VarSymbol {
-                    name: param.to_string(),
-                    symbol_type: TypeSymbol::Unknown,
-                }),
-            );
+            self.scope.borrow_mut().insert(param, Symbol::VarSymbol);
         }
 
         self.visit(&node.block)?;
@@ -250,19 +493,23 @@ impl SemanticAnalyzer {
             Node::Compound(nodes) => self.visit_compound(nodes),
             Node::VariabeDecleration(node) => self.visit_variable_decleration(node),
             Node::FunctionDecleration(node) => self.visit_function_decleration(node),
+            Node::EnumDecleration(node) => self.visit_enum_decleration(node),
             Node::Expression(node) => self.visit_expression(node),
             Node::Block(nodes) => self.visit_block(nodes),
+            Node::ForIn(node) => self.visit_for_in(node),
+            Node::Yield(value) => self.visit(value),
             node => self.visit_expression(node),
         }
     }
 
+    #[cfg(test)]
     pub fn analyze(&mut self, node: &Node) -> SResult {
-        self.interpreter_options = InterpreterOptions::new();
-        self.visit(node)
+        self.analyze_with_options(node, &InterpreterOptions::new())
     }
 
     pub fn analyze_with_options(&mut self, node: &Node, option: &InterpreterOptions) -> SResult {
         self.interpreter_options = option.clone();
+        self.warnings.clear();
         self.visit(node)
     }
 }
@@ -298,4 +545,61 @@ mod tests {
         let ast = parser.parse().unwrap();
         semantic_analyzer.analyze(&ast).unwrap();
     }
+
+    #[test]
+    fn should_warn_about_unreachable_match_arms() {
+        let mut parser = Parser::new(
+            "match 1 { 1 | 2 => 'a', x if x > 2 => 'b', 2 => 'c', _ => 'd', 3 => 'e' }",
+        );
+        let mut semantic_analyzer = SemanticAnalyzer::new();
+        let ast = parser.parse().unwrap();
+        semantic_analyzer.analyze(&ast).unwrap();
+        assert_eq!(
+            semantic_analyzer.warnings,
+            vec![
+                NekoWarning::UnreachablePattern(String::from(
+                    "Arm '2' will never be matched, previous arms cover it"
+                )),
+                NekoWarning::UnreachablePattern(String::from(
+                    "Arm '3' will never be matched, previous arms cover it"
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_scope_match_bindings_to_their_arm() {
+        let mut parser = Parser::new("match 1 { x => x, _ => x }");
+        let mut semantic_analyzer = SemanticAnalyzer::new();
+        let ast = parser.parse().unwrap();
+        semantic_analyzer.analyze(&ast).unwrap();
+    }
+
+    #[test]
+    fn should_catch_unknown_enum_variants() {
+        let mut semantic_analyzer = SemanticAnalyzer::new();
+        let ast = Parser::new("enum Shape { Circle(r), Empty }")
+            .parse()
+            .unwrap();
+        semantic_analyzer.analyze(&ast).unwrap();
+
+        for (source, error) in [
+            (
+                "Shape.Circel(1);",
+                NekoError::ReferenceError(String::from("Enum Shape has no variant 'Circel'")),
+            ),
+            (
+                "Shape.Circle(1, 2);",
+                NekoError::TypeError(String::from("Shape.Circle expects 1 arguments, got 2")),
+            ),
+            (
+                "match 1 { Shape.Emtpy => 0 }",
+                NekoError::ReferenceError(String::from("Enum Shape has no variant 'Emtpy'")),
+            ),
+        ] {
+            let ast = Parser::new(source).parse().unwrap();
+            assert_eq!(semantic_analyzer.analyze(&ast), Err(error));
+        }
+    }
 }
@@ -0,0 +1,160 @@
+use crate::{
+    enviroment::Value,
+    misc::NekoError,
+    stdlib::{convert::type_of, json::from_json},
+};
+use serde::{
+    de::DeserializeOwned,
+    ser::{Error, SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::Value as Json;
+use std::{cell::RefCell, rc::Rc};
+
+/// A value being serialized, `seen` holds the objects and lists currently being
+/// serialized so that cycles are reported instead of recursing forever.
+struct Tracked<'a> {
+    value: &'a Value,
+    seen: &'a RefCell<Vec<*const ()>>,
+}
+
+impl Tracked<'_> {
+    fn enter<E: Error>(&self, pointer: *const ()) -> Result<(), E> {
+        if self.seen.borrow().contains(&pointer) {
+            Err(E::custom(format!(
+                "Cannot serialize a cyclic {}",
+                type_of(self.value)
+            )))
+        } else {
+            self.seen.borrow_mut().push(pointer);
+            Ok(())
+        }
+    }
+}
+
+impl Serialize for Tracked<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            Value::None => serializer.serialize_unit(),
+            Value::Boolean(boolean) => serializer.serialize_bool(*boolean),
+            Value::String(string) => serializer.serialize_str(string),
+            Value::Number(num) if num.fract() == 0.0 && num.abs() < 9007199254740992.0 => {
+                serializer.serialize_i64(*num as i64)
+            }
+            Value::Number(num) => serializer.serialize_f64(*num),
+            Value::List(items) => {
+                self.enter(Rc::as_ptr(items) as *const ())?;
+                let result = (|| {
+                    let items = items.borrow();
+                    let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                    for item in items.iter() {
+                        seq.serialize_element(&Tracked {
+                            value: item,
+                            seen: self.seen,
+                        })?;
+                    }
+                    seq.end()
+                })();
+                self.seen.borrow_mut().pop();
+                result
+            }
+            Value::Object(obj) => {
+                self.enter(Rc::as_ptr(obj) as *const ())?;
+                let result = (|| {
+                    let obj = obj.borrow();
+                    let mut map = serializer.serialize_map(Some(obj.len()))?;
+                    for (key, value) in obj.iter() {
+                        map.serialize_entry(
+                            key,
+                            &Tracked {
+                                value,
+                                seen: self.seen,
+                            },
+                        )?;
+                    }
+                    map.end()
+                })();
+                self.seen.borrow_mut().pop();
+                result
+            }
+            Value::Function(..) | Value::Iterator(_) | Value::Regex(_) | Value::Variant(_) => {
+                Err(S::Error::custom(format!("Cannot serialize {}", self.value)))
+            }
+        }
+    }
+}
+
+/// Objects serialize as maps and lists as sequences, functions, iterators,
+/// regexes, variants and cycles are errors.
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Tracked {
+            value: self,
+            seen: &RefCell::new(vec![]),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Json::deserialize(deserializer).map(from_json)
+    }
+}
+
+/// Converts any serializable Rust value into a Neko value.
+pub fn to_value<T: Serialize>(value: &T) -> Result<Value, NekoError> {
+    serde_json::to_value(value)
+        .map(from_json)
+        .map_err(|err| NekoError::TypeError(err.to_string()))
+}
+
+/// Converts a Neko value into any deserializable Rust value.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, NekoError> {
+    serde_json::to_value(&value)
+        .and_then(serde_json::from_value)
+        .map_err(|err| NekoError::TypeError(err.to_string()))
+}
+
+#[test]
+fn should_convert_serde_types() {
+    use crate::interpreter::Interpreter;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        id: u32,
+        items: Vec<String>,
+        note: Option<String>,
+    }
+
+    let mut interpreter = Interpreter::new();
+    let order = Order {
+        id: 7,
+        items: vec![String::from("tea")],
+        note: None,
+    };
+    interpreter.set_global("order", to_value(&order).unwrap());
+    let result = interpreter
+        .interpret(
+            "order.items.push('cake'); order.note = 'fragile'; order.id = order.id + 1; order;",
+        )
+        .unwrap();
+
+    assert_eq!(
+        from_value::<Order>(result).unwrap(),
+        Order {
+            id: 8,
+            items: vec![String::from("tea"), String::from("cake")],
+            note: Some(String::from("fragile")),
+        }
+    );
+    assert!(from_value::<Order>(Value::Number(1.0)).is_err());
+
+    let function = interpreter.interpret("|x| x;").unwrap();
+    assert!(serde_json::to_string(&function).is_err());
+    let cycle = interpreter
+        .interpret("let cycle = { }; cycle.me = cycle; cycle;")
+        .unwrap();
+    assert!(serde_json::to_string(&cycle).is_err());
+}
@@ -0,0 +1,203 @@
+use super::built_in;
+use crate::{
+    enviroment::{Env, Value},
+    interpreter::{output, to_bool, Interpreter},
+    misc::NekoError,
+};
+use std::rc::Rc;
+
+pub fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Number(_) => "number",
+        Value::Boolean(_) => "boolean",
+        Value::String(_) => "string",
+        Value::Object(_) => "object",
+        Value::List(_) => "list",
+        Value::Iterator(_) => "iterator",
+        Value::Regex(_) => "regex",
+        Value::Function(..) => "function",
+        Value::Variant(_) => "variant",
+        Value::None => "none",
+    }
+}
+
+fn to_number(value: &Value) -> Result<f64, NekoError> {
+    match value {
+        Value::Number(num) => Ok(*num),
+        Value::Boolean(boolean) => Ok(if *boolean { 1.0 } else { 0.0 }),
+        Value::String(string) => string
+            .trim()
+            .parse()
+            .map_err(|_| NekoError::TypeError(format!("Cannot convert {:?} to a number", string))),
+        other => Err(NekoError::TypeError(format!(
+            "Cannot convert {} to a number",
+            type_of(other)
+        ))),
+    }
+}
+
+/// Charges about the length `value` is displayed with before it is built. Lists
+/// and objects shared in several places are displayed, and counted, each time,
+/// so counting stops as soon as the allocation budget is exhausted.
+fn allocate_display(interpreter: &mut Interpreter, value: &Value) -> Result<(), NekoError> {
+    // `seen` holds the lists and objects being counted, cycles are shown as `[...]`.
+    fn display_len(value: &Value, limit: usize, total: &mut usize, seen: &mut Vec<*const ()>) {
+        if *total > limit {
+            return;
+        }
+        let pointer = match value {
+            Value::List(list) => Some(Rc::as_ptr(list) as *const ()),
+            Value::Object(obj) => Some(Rc::as_ptr(obj) as *const ()),
+            _ => None,
+        };
+        if let Some(pointer) = pointer {
+            if seen.contains(&pointer) {
+                *total += 5;
+                return;
+            }
+            seen.push(pointer);
+        }
+        match value {
+            Value::String(string) => *total += string.len() + 2,
+            Value::List(list) => {
+                *total += 2;
+                for item in list.borrow().iter() {
+                    *total += 2;
+                    display_len(item, limit, total, seen);
+                }
+            }
+            Value::Object(obj) => {
+                *total += 2;
+                for (key, value) in obj.borrow().iter() {
+                    *total += key.len() + 4;
+                    display_len(value, limit, total, seen);
+                }
+            }
+            Value::Variant(variant) => {
+                *total += variant.enum_name.len() + variant.name.len() + 3;
+                for (_, value) in &variant.fields {
+                    *total += 2;
+                    display_len(value, limit, total, seen);
+                }
+            }
+            _ => *total += 24,
+        }
+        if pointer.is_some() {
+            seen.pop();
+        }
+    }
+
+    match interpreter.allocation_left() {
+        Some(limit) => {
+            let mut total = 0;
+            display_len(value, limit, &mut total, &mut vec![]);
+            interpreter.allocate(total)
+        }
+        None => Ok(()),
+    }
+}
+
+pub fn functions(env: &Env) -> Vec<(&'static str, Value)> {
+    vec![
+        (
+            "type_of",
+            built_in(
+                "type_of",
+                |_, args| {
+                    let value = args.first().unwrap_or(&Value::None);
+                    Ok(Value::String(String::from(type_of(value))))
+                },
+                env,
+            ),
+        ),
+        (
+            "str",
+            built_in(
+                "str",
+                |interpreter, args| {
+                    let value = args.first().unwrap_or(&Value::None);
+                    Ok(Value::String(match interpreter.str_metamethod(value)? {
+                        Some(string) => string,
+                        None => {
+                            allocate_display(interpreter, value)?;
+                            value.stringify()
+                        }
+                    }))
+                },
+                env,
+            ),
+        ),
+        (
+            "num",
+            built_in(
+                "num",
+                |_, args| to_number(args.first().unwrap_or(&Value::None)).map(Value::Number),
+                env,
+            ),
+        ),
+        (
+            "bool",
+            built_in(
+                "bool",
+                |_, args| {
+                    Ok(Value::Boolean(to_bool(
+                        args.first().unwrap_or(&Value::None),
+                    )))
+                },
+                env,
+            ),
+        ),
+        (
+            "repr",
+            built_in(
+                "repr",
+                |interpreter, args| {
+                    let value = args.first().unwrap_or(&Value::None);
+                    allocate_display(interpreter, value)?;
+                    Ok(Value::String(output(value, false)))
+                },
+                env,
+            ),
+        ),
+    ]
+}
+
+#[test]
+fn should_inspect_and_convert_values() {
+    use crate::interpreter::Interpreter;
+
+    let mut interpreter = Interpreter::new();
+    let cases = [
+        (
+            "type_of(1) + type_of('') + type_of([]);",
+            "numberstringlist",
+        ),
+        (
+            "type_of(none) + type_of(print) + type_of({});",
+            "nonefunctionobject",
+        ),
+        ("str(1.5) + str(true) + str('x');", "1.5truex"),
+        ("str({ __str: |self| 'custom' });", "custom"),
+        ("repr('a') + repr([1, none]);", "\"a\"[1, none]"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(
+            interpreter.interpret(input).unwrap(),
+            Value::String(String::from(expected)),
+            "{}",
+            input
+        );
+    }
+    assert_eq!(
+        interpreter.interpret("num(' 5 ') + 1;").unwrap(),
+        Value::Number(6.0)
+    );
+    assert_eq!(
+        interpreter.interpret("bool('') or bool([]);").unwrap(),
+        Value::Boolean(false)
+    );
+    assert_eq!(
+        interpreter.interpret("num('five');").unwrap_err(),
+        NekoError::TypeError(String::from("Cannot convert \"five\" to a number"))
+    );
+}
@@ -0,0 +1,262 @@
+use super::{built_in, list, object, string_arg};
+use crate::{
+    enviroment::{Env, Value},
+    interpreter::{Interpreter, VALUE_SIZE},
+    misc::NekoError,
+};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Component, PathBuf},
+};
+
+fn io_error(path: &str, err: io::Error) -> NekoError {
+    NekoError::IOError(format!("{}: {}", path, err))
+}
+
+fn roots(interpreter: &Interpreter, name: &str) -> Result<Vec<PathBuf>, NekoError> {
+    let roots = &interpreter.options().policy.fs_roots;
+    if roots.is_empty() {
+        return Err(NekoError::PermissionError(format!(
+            "{} is not allowed, file system access is disabled (see --allow-fs)",
+            name
+        )));
+    }
+    roots
+        .iter()
+        .map(|root| {
+            root.canonicalize()
+                .map_err(|err| io_error(&root.display().to_string(), err))
+        })
+        .collect()
+}
+
+/// Resolves `path` against the directories allowed by `Policy::fs_roots`,
+/// refusing anything that ends up outside of them, including through symlinks.
+fn resolve(interpreter: &Interpreter, name: &str, path: &str) -> Result<PathBuf, NekoError> {
+    let roots = roots(interpreter, name)?;
+
+    let joined = roots[0].join(path);
+    let resolved = match joined.canonicalize() {
+        Ok(resolved) => resolved,
+        // A dangling symlink would be followed when the file is created.
+        Err(_) if fs::symlink_metadata(&joined).is_ok_and(|meta| meta.file_type().is_symlink()) => {
+            return Err(NekoError::PermissionError(format!(
+                "{} is not allowed, '{}' is a symlink to a missing file",
+                name, path
+            )))
+        }
+        // The file may not exist yet, its parent has to.
+        Err(_) => match (joined.parent(), joined.components().next_back()) {
+            (Some(parent), Some(Component::Normal(file_name))) => parent
+                .canonicalize()
+                .map_err(|err| io_error(path, err))?
+                .join(file_name),
+            _ => joined,
+        },
+    };
+
+    if roots.iter().any(|root| resolved.starts_with(root)) {
+        Ok(resolved)
+    } else {
+        Err(NekoError::PermissionError(format!(
+            "{} is not allowed, '{}' is outside of the allowed directories",
+            name, path
+        )))
+    }
+}
+
+fn write(
+    interpreter: &mut Interpreter,
+    args: Vec<Value>,
+    append: bool,
+) -> Result<Value, NekoError> {
+    let name = if append { "fs.append" } else { "fs.write" };
+    let path = string_arg(name, &args, 0)?;
+    let content = string_arg(name, &args, 1)?;
+    let resolved = resolve(interpreter, name, path)?;
+    fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(resolved)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|err| io_error(path, err))?;
+    Ok(Value::None)
+}
+
+pub fn module(env: &Env) -> Value {
+    object(vec![
+        (
+            "read",
+            built_in(
+                "read",
+                |interpreter, args| {
+                    let path = string_arg("fs.read", &args, 0)?;
+                    let resolved = resolve(interpreter, "fs.read", path)?;
+                    let size = fs::metadata(&resolved).map_err(|err| io_error(path, err))?;
+                    interpreter.allocate(size.len() as usize)?;
+                    fs::read_to_string(resolved)
+                        .map(Value::String)
+                        .map_err(|err| io_error(path, err))
+                },
+                env,
+            ),
+        ),
+        (
+            "write",
+            built_in(
+                "write",
+                |interpreter, args| write(interpreter, args, false),
+                env,
+            ),
+        ),
+        (
+            "append",
+            built_in(
+                "append",
+                |interpreter, args| write(interpreter, args, true),
+                env,
+            ),
+        ),
+        (
+            "exists",
+            built_in(
+                "exists",
+                |interpreter, args| {
+                    let path = string_arg("fs.exists", &args, 0)?;
+                    match resolve(interpreter, "fs.exists", path) {
+                        Ok(resolved) => Ok(Value::Boolean(resolved.exists())),
+                        Err(NekoError::IOError(_)) => Ok(Value::Boolean(false)),
+                        Err(err) => Err(err),
+                    }
+                },
+                env,
+            ),
+        ),
+        (
+            "list_dir",
+            built_in(
+                "list_dir",
+                |interpreter, args| {
+                    let path = match args.first() {
+                        Some(_) => string_arg("fs.list_dir", &args, 0)?,
+                        None => ".",
+                    };
+                    let resolved = resolve(interpreter, "fs.list_dir", path)?;
+                    let mut names = fs::read_dir(resolved)
+                        .and_then(|entries| {
+                            entries
+                                .map(|entry| {
+                                    entry.map(|entry| {
+                                        entry.file_name().to_string_lossy().into_owned()
+                                    })
+                                })
+                                .collect::<io::Result<Vec<String>>>()
+                        })
+                        .map_err(|err| io_error(path, err))?;
+                    interpreter.allocate(names.iter().map(|name| name.len() + VALUE_SIZE).sum())?;
+                    names.sort();
+                    Ok(interpreter.created(list(names.into_iter().map(Value::String).collect())))
+                },
+                env,
+            ),
+        ),
+        (
+            "remove",
+            built_in(
+                "remove",
+                |interpreter, args| {
+                    let path = string_arg("fs.remove", &args, 0)?;
+                    let resolved = resolve(interpreter, "fs.remove", path)?;
+                    if roots(interpreter, "fs.remove")?.contains(&resolved) {
+                        return Err(NekoError::PermissionError(String::from(
+                            "fs.remove cannot remove the allowed directory itself",
+                        )));
+                    }
+                    if resolved.is_dir() {
+                        fs::remove_dir(resolved)
+                    } else {
+                        fs::remove_file(resolved)
+                    }
+                    .map_err(|err| io_error(path, err))?;
+                    Ok(Value::None)
+                },
+                env,
+            ),
+        ),
+    ])
+}
+
+#[test]
+fn should_confine_fs_to_the_allowed_directory() {
+    use crate::interpreter_option::InterpreterOptions;
+
+    let mut sandboxed = Interpreter::new();
+    assert!(matches!(
+        sandboxed.interpret("fs.read('Cargo.toml');"),
+        Err(NekoError::PermissionError(_))
+    ));
+
+    let root = std::env::temp_dir().join(format!("neko-fs-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    let mut options = InterpreterOptions::new();
+    options.policy.fs_roots = vec![root.clone()];
+    let mut interpreter = Interpreter::with_options(options);
+
+    interpreter
+        .interpret("fs.write('notes.txt', 'a'); fs.append('notes.txt', 'b');")
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("fs.read('notes.txt');").unwrap(),
+        Value::String(String::from("ab"))
+    );
+    assert_eq!(
+        interpreter.interpret("fs.list_dir()[0];").unwrap(),
+        Value::String(String::from("notes.txt"))
+    );
+    assert!(matches!(
+        interpreter.interpret("fs.read('../outside.txt');"),
+        Err(NekoError::PermissionError(_))
+    ));
+    interpreter.interpret("fs.remove('notes.txt');").unwrap();
+    assert_eq!(
+        interpreter.interpret("fs.exists('notes.txt');").unwrap(),
+        Value::Boolean(false)
+    );
+    assert!(matches!(
+        interpreter.interpret("fs.read('notes.txt');"),
+        Err(NekoError::IOError(_))
+    ));
+
+    #[cfg(unix)]
+    {
+        let outside = std::env::temp_dir().join(format!("neko-escaped-{}", std::process::id()));
+        std::os::unix::fs::symlink(&outside, root.join("dangling")).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("existing")).unwrap();
+        for input in &[
+            "fs.write('dangling', 'pwned');",
+            "fs.append('dangling', 'pwned');",
+        ] {
+            assert!(
+                matches!(
+                    interpreter.interpret(input),
+                    Err(NekoError::PermissionError(_))
+                ),
+                "{}",
+                input
+            );
+        }
+        assert!(!outside.exists());
+        fs::write(&outside, "safe").unwrap();
+        assert!(matches!(
+            interpreter.interpret("fs.write('existing', 'pwned');"),
+            Err(NekoError::PermissionError(_))
+        ));
+        assert_eq!(fs::read_to_string(&outside).unwrap(), "safe");
+        fs::remove_file(outside).unwrap();
+    }
+
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,98 @@
+use super::{built_in, string_arg};
+use crate::{
+    enviroment::{Env, Value},
+    generator::Generator,
+    misc::NekoError,
+};
+use std::{cell::RefCell, io, rc::Rc};
+
+pub fn io_error(err: io::Error) -> NekoError {
+    NekoError::IOError(format!("stdin: {}", err))
+}
+
+pub fn functions(env: &Env) -> Vec<(&'static str, Value)> {
+    vec![
+        (
+            "input",
+            built_in(
+                "input",
+                |interpreter, args| {
+                    let prompt = match args.first() {
+                        Some(_) => string_arg("input", &args, 0)?,
+                        None => "",
+                    };
+                    let line = interpreter.input().read_line(prompt).map_err(io_error)?;
+                    interpreter.allocate(line.as_ref().map_or(0, String::len))?;
+                    Ok(line.map_or(Value::None, Value::String))
+                },
+                env,
+            ),
+        ),
+        (
+            "read_line",
+            built_in(
+                "read_line",
+                |interpreter, _| {
+                    let line = interpreter.input().read_line("").map_err(io_error)?;
+                    interpreter.allocate(line.as_ref().map_or(0, String::len))?;
+                    Ok(line.map_or(Value::None, Value::String))
+                },
+                env,
+            ),
+        ),
+        (
+            "read_all",
+            built_in(
+                "read_all",
+                |interpreter, _| {
+                    let content = interpreter.input().read_all().map_err(io_error)?;
+                    interpreter.allocate(content.len())?;
+                    Ok(Value::String(content))
+                },
+                env,
+            ),
+        ),
+        (
+            "lines",
+            built_in(
+                "lines",
+                |_, _| Ok(Value::Iterator(Rc::new(RefCell::new(Generator::Lines)))),
+                env,
+            ),
+        ),
+    ]
+}
+
+#[test]
+fn should_read_from_the_input_source() {
+    use crate::{input::StringInput, interpreter::Interpreter};
+
+    let mut interpreter = Interpreter::new();
+    interpreter.set_input(Box::new(StringInput::new("neko\n1\n2\n3\n")));
+    assert_eq!(
+        interpreter.interpret("input('name? ');").unwrap(),
+        Value::String(String::from("neko"))
+    );
+    assert_eq!(
+        interpreter.interpret("read_line();").unwrap(),
+        Value::String(String::from("1"))
+    );
+    interpreter
+        .interpret("let seen = []; for line in lines() { seen.push(line); }")
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("seen.len();").unwrap(),
+        Value::Number(2.0)
+    );
+    assert_eq!(interpreter.interpret("read_line();").unwrap(), Value::None);
+
+    interpreter.set_input(Box::new(StringInput::new("a\nb\n")));
+    assert_eq!(
+        interpreter.interpret("read_all();").unwrap(),
+        Value::String(String::from("a\nb\n"))
+    );
+    assert_eq!(
+        interpreter.interpret("read_all();").unwrap(),
+        Value::String(String::new())
+    );
+}
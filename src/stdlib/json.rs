@@ -0,0 +1,268 @@
+use super::{built_in, list, number_arg, object, string_arg};
+use crate::{
+    enviroment::{Env, Value},
+    interpreter::{Interpreter, VALUE_SIZE},
+    misc::NekoError,
+};
+use serde::Serialize;
+use serde_json::{ser::PrettyFormatter, Map, Number, Serializer, Value as Json};
+use std::{cell::RefCell, collections::HashMap, io, rc::Rc};
+
+pub fn from_json(json: Json) -> Value {
+    match json {
+        Json::Null => Value::None,
+        Json::Bool(boolean) => Value::Boolean(boolean),
+        Json::Number(num) => Value::Number(num.as_f64().unwrap_or(f64::NAN)),
+        Json::String(string) => Value::String(string),
+        Json::Array(items) => list(items.into_iter().map(from_json).collect()),
+        Json::Object(map) => {
+            let values: HashMap<String, Box<Value>> = map
+                .into_iter()
+                .map(|(key, value)| (key, Box::new(from_json(value))))
+                .collect();
+            Value::Object(Rc::new(RefCell::new(values)))
+        }
+    }
+}
+
+/// Records the lists and objects in `value`, all built by `from_json`, as
+/// created by the current evaluation.
+fn created(interpreter: &mut Interpreter, value: Value) -> Value {
+    match &value {
+        Value::List(items) => {
+            for item in items.borrow().iter() {
+                created(interpreter, item.clone());
+            }
+        }
+        Value::Object(values) => {
+            for value in values.borrow().values() {
+                created(interpreter, *value.clone());
+            }
+        }
+        _ => {}
+    }
+    interpreter.created(value)
+}
+
+/// Approximate bytes `from_json` allocates for `json`.
+fn json_size(json: &Json) -> usize {
+    VALUE_SIZE
+        + match json {
+            Json::String(string) => string.len(),
+            Json::Array(items) => items.iter().map(json_size).sum(),
+            Json::Object(map) => map
+                .iter()
+                .map(|(key, value)| key.len() + json_size(value))
+                .sum(),
+            _ => 0,
+        }
+}
+
+/// Converts a value to JSON, `seen` holds the objects and lists currently being
+/// converted so that cycles are reported instead of recursing forever.
+fn to_json(
+    interpreter: &mut Interpreter,
+    value: &Value,
+    seen: &mut Vec<*const ()>,
+) -> Result<Json, NekoError> {
+    interpreter.allocate(VALUE_SIZE)?;
+    match value {
+        Value::None => Ok(Json::Null),
+        Value::Boolean(boolean) => Ok(Json::Bool(*boolean)),
+        Value::String(string) => {
+            interpreter.allocate(string.len())?;
+            Ok(Json::String(string.clone()))
+        }
+        Value::Number(num) if num.fract() == 0.0 && num.abs() < 9007199254740992.0 => {
+            Ok(Json::Number(Number::from(*num as i64)))
+        }
+        Value::Number(num) => Number::from_f64(*num)
+            .map(Json::Number)
+            .ok_or_else(|| NekoError::TypeError(format!("Cannot convert {} to JSON", num))),
+        Value::List(items) => {
+            let pointer = Rc::as_ptr(items) as *const ();
+            if seen.contains(&pointer) {
+                return Err(NekoError::TypeError(String::from(
+                    "Cannot convert a cyclic list to JSON",
+                )));
+            }
+            seen.push(pointer);
+            let items = items
+                .borrow()
+                .iter()
+                .map(|item| to_json(interpreter, item, seen))
+                .collect::<Result<Vec<Json>, NekoError>>();
+            seen.pop();
+            Ok(Json::Array(items?))
+        }
+        Value::Object(obj) => {
+            let pointer = Rc::as_ptr(obj) as *const ();
+            if seen.contains(&pointer) {
+                return Err(NekoError::TypeError(String::from(
+                    "Cannot convert a cyclic object to JSON",
+                )));
+            }
+            seen.push(pointer);
+            let mut map = Map::new();
+            let result = obj.borrow().iter().try_for_each(|(key, value)| {
+                interpreter.allocate(key.len())?;
+                map.insert(key.clone(), to_json(interpreter, value, seen)?);
+                Ok(())
+            });
+            seen.pop();
+            result.map(|_| Json::Object(map))
+        }
+        Value::Function(..) | Value::Iterator(_) | Value::Regex(_) | Value::Variant(_) => Err(
+            NekoError::TypeError(format!("Cannot convert {} to JSON", value)),
+        ),
+    }
+}
+
+/// Indents are capped at 10 spaces, like JavaScript's `JSON.stringify`.
+fn indent_arg(args: &[Value]) -> Result<usize, NekoError> {
+    let indent = number_arg("json.stringify", args, 1)?;
+    if !indent.is_finite() || indent.fract() != 0.0 || indent < 0.0 {
+        return Err(NekoError::TypeError(format!(
+            "json.stringify expects a non-negative integer indent, got {}",
+            indent
+        )));
+    }
+    Ok(indent.min(10.0) as usize)
+}
+
+/// Collects serialized JSON, refusing to grow past `limit` bytes. Indentation
+/// can make the output much larger than the value, so it is bounded while written.
+struct Limited {
+    output: Vec<u8>,
+    limit: usize,
+    /// Bytes written or refused, what the output would have needed.
+    needed: usize,
+}
+
+impl io::Write for Limited {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.needed = self.needed.saturating_add(buf.len());
+        if self.needed > self.limit {
+            return Err(io::Error::other(
+                "JSON output exceeds the allocation budget",
+            ));
+        }
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn stringify(
+    interpreter: &mut Interpreter,
+    value: &Value,
+    indent: Option<usize>,
+) -> Result<String, NekoError> {
+    let json = to_json(interpreter, value, &mut vec![])?;
+    let mut output = Limited {
+        output: vec![],
+        limit: interpreter.allocation_left().unwrap_or(usize::MAX),
+        needed: 0,
+    };
+    let result = match indent {
+        None => serde_json::to_writer(&mut output, &json),
+        Some(indent) => {
+            let indent = " ".repeat(indent);
+            let mut serializer = Serializer::with_formatter(
+                &mut output,
+                PrettyFormatter::with_indent(indent.as_bytes()),
+            );
+            json.serialize(&mut serializer)
+        }
+    };
+    interpreter.allocate(output.needed)?;
+    result.map_err(|err| NekoError::TypeError(err.to_string()))?;
+    Ok(String::from_utf8(output.output).expect("JSON output is valid UTF-8"))
+}
+
+pub fn module(env: &Env) -> Value {
+    object(vec![
+        (
+            "parse",
+            built_in(
+                "parse",
+                |interpreter, args| {
+                    let text = string_arg("json.parse", &args, 0)?;
+                    let json: Json = serde_json::from_str(text)
+                        .map_err(|err| NekoError::TypeError(format!("Invalid JSON: {}", err)))?;
+                    interpreter.allocate(json_size(&json))?;
+                    Ok(created(interpreter, from_json(json)))
+                },
+                env,
+            ),
+        ),
+        (
+            "stringify",
+            built_in(
+                "stringify",
+                |interpreter, args| {
+                    let value = args.first().unwrap_or(&Value::None);
+                    let indent = match args.get(1) {
+                        Some(_) => Some(indent_arg(&args)?),
+                        None => None,
+                    };
+                    stringify(interpreter, value, indent).map(Value::String)
+                },
+                env,
+            ),
+        ),
+    ])
+}
+
+#[test]
+fn should_parse_and_stringify_json() {
+    use crate::interpreter::Interpreter;
+
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(
+            r#"let config = json.parse('{"name": "neko", "tags": ["a", 1.5, null], "ok": true}');"#,
+        )
+        .unwrap();
+    assert_eq!(
+        interpreter
+            .interpret("config.name + config.tags[0];")
+            .unwrap(),
+        Value::String(String::from("nekoa"))
+    );
+    assert_eq!(
+        interpreter.interpret("config.tags[1];").unwrap(),
+        Value::Number(1.5)
+    );
+    assert_eq!(
+        interpreter.interpret("json.stringify(config);").unwrap(),
+        Value::String(String::from(
+            r#"{"name":"neko","ok":true,"tags":["a",1.5,null]}"#
+        ))
+    );
+    assert_eq!(
+        interpreter
+            .interpret("json.stringify([1, { a: 2 }], 2);")
+            .unwrap(),
+        Value::String(String::from("[\n  1,\n  {\n    \"a\": 2\n  }\n]"))
+    );
+    assert_eq!(
+        interpreter
+            .interpret("json.stringify([1], 1000000000000000000000000000000);")
+            .unwrap(),
+        Value::String(format!("[\n{}1\n]", " ".repeat(10)))
+    );
+    assert!(interpreter.interpret("json.stringify(1, -1);").is_err());
+    assert!(interpreter.interpret("json.stringify(1, 1.5);").is_err());
+    assert!(interpreter.interpret("json.parse('{');").is_err());
+    assert!(interpreter.interpret("json.stringify(print);").is_err());
+    assert_eq!(
+        interpreter
+            .interpret("let cyclic = { a: 1 }; cyclic.self = cyclic; json.stringify(cyclic);")
+            .unwrap_err(),
+        NekoError::TypeError(String::from("Cannot convert a cyclic object to JSON"))
+    );
+}
@@ -0,0 +1,210 @@
+use crate::{
+    enviroment::Value,
+    interpreter::{to_bool, IResult, Interpreter, Method, VALUE_SIZE},
+    misc::NekoError,
+};
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+fn receiver(value: &Value) -> &Rc<RefCell<Vec<Value>>> {
+    match value {
+        Value::List(list) => list,
+        _ => unreachable!(),
+    }
+}
+
+fn callback(name: &str, args: &[Value]) -> Result<Value, NekoError> {
+    match args.first() {
+        Some(function @ Value::Function(..)) | Some(function @ Value::Object(_)) => {
+            Ok(function.clone())
+        }
+        Some(other) => Err(NekoError::TypeError(format!(
+            "{} expects a function, got {}",
+            name, other
+        ))),
+        None => Err(NekoError::TypeError(format!("{} expects a function", name))),
+    }
+}
+
+/// Items are copied out before calling back into Neko, so callbacks may
+/// freely modify the list they are iterating.
+fn items(list: &Value) -> Vec<Value> {
+    receiver(list).borrow().clone()
+}
+
+fn map(interpreter: &mut Interpreter, list: Value, args: Vec<Value>) -> IResult {
+    let function = callback("map", &args)?;
+    interpreter.allocate(receiver(&list).borrow().len() * VALUE_SIZE)?;
+    let mut mapped = vec![];
+    for (index, item) in items(&list).into_iter().enumerate() {
+        mapped.push(
+            interpreter.call_function(function.clone(), vec![item, Value::Number(index as f64)])?,
+        );
+    }
+    Ok(interpreter.created(super::list(mapped)))
+}
+
+fn filter(interpreter: &mut Interpreter, list: Value, args: Vec<Value>) -> IResult {
+    let function = callback("filter", &args)?;
+    let mut kept = vec![];
+    for (index, item) in items(&list).into_iter().enumerate() {
+        let keep = interpreter.call_function(
+            function.clone(),
+            vec![item.clone(), Value::Number(index as f64)],
+        )?;
+        if to_bool(&keep) {
+            interpreter.allocate(VALUE_SIZE)?;
+            kept.push(item)
+        }
+    }
+    Ok(interpreter.created(super::list(kept)))
+}
+
+fn for_each(interpreter: &mut Interpreter, list: Value, args: Vec<Value>) -> IResult {
+    let function = callback("for_each", &args)?;
+    for (index, item) in items(&list).into_iter().enumerate() {
+        interpreter.call_function(function.clone(), vec![item, Value::Number(index as f64)])?;
+    }
+    Ok(Value::None)
+}
+
+/// The default order, NaN sorts after every other number.
+fn compare(a: &Value, b: &Value) -> Result<Ordering, NekoError> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => Ok(match (a.is_nan(), b.is_nan()) {
+            (false, false) => a.partial_cmp(b).unwrap(),
+            (a_nan, b_nan) => a_nan.cmp(&b_nan),
+        }),
+        (Value::String(a), Value::String(b)) => Ok(a.cmp(b)),
+        _ => Err(NekoError::TypeError(format!(
+            "Cannot compare {} and {}, pass a comparator to sort",
+            a, b
+        ))),
+    }
+}
+
+/// A stable merge sort. Unlike `slice::sort_by` it cannot panic when a script
+/// comparator is inconsistent, the resulting order is then unspecified.
+fn merge_sort<F>(mut items: Vec<Value>, compare: &mut F) -> Result<Vec<Value>, NekoError>
+where
+    F: FnMut(&Value, &Value) -> Result<Ordering, NekoError>,
+{
+    if items.len() <= 1 {
+        return Ok(items);
+    }
+    let right = items.split_off(items.len() / 2);
+    let left = merge_sort(items, compare)?;
+    let right = merge_sort(right, compare)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        if compare(a, b)? == Ordering::Greater {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+/// Sorts in place, by an optional comparator returning a negative number,
+/// zero or a positive number like in most languages.
+fn sort(interpreter: &mut Interpreter, list: Value, args: Vec<Value>) -> IResult {
+    let comparator = match args.first() {
+        Some(_) => Some(callback("sort", &args)?),
+        None => None,
+    };
+    interpreter.check_mutable(&list)?;
+    let sorted = merge_sort(items(&list), &mut |a, b| match &comparator {
+        Some(comparator) => {
+            match interpreter.call_function(comparator.clone(), vec![a.clone(), b.clone()])? {
+                Value::Number(num) => Ok(num.partial_cmp(&0.0).unwrap_or(Ordering::Equal)),
+                other => Err(NekoError::TypeError(format!(
+                    "sort comparator must return a Number, got {}",
+                    other
+                ))),
+            }
+        }
+        None => compare(a, b),
+    })?;
+    *receiver(&list).borrow_mut() = sorted;
+    Ok(list)
+}
+
+pub fn method(name: &str) -> Option<Method> {
+    match name {
+        "len" => Some(|_, list, _| Ok(Value::Number(receiver(&list).borrow().len() as f64))),
+        "push" => Some(|interpreter, list, args| {
+            interpreter.check_mutable(&list)?;
+            interpreter.allocate(args.len() * VALUE_SIZE)?;
+            receiver(&list).borrow_mut().extend(args);
+            Ok(Value::None)
+        }),
+        "pop" => Some(|interpreter, list, _| {
+            interpreter.check_mutable(&list)?;
+            Ok(receiver(&list).borrow_mut().pop().unwrap_or(Value::None))
+        }),
+        "map" => Some(map),
+        "filter" => Some(filter),
+        "for_each" => Some(for_each),
+        "sort" => Some(sort),
+        _ => None,
+    }
+}
+
+#[test]
+fn should_call_higher_order_list_methods() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret("let xs = [3, 1, 2]; let total = { sum: 0 };")
+        .unwrap();
+    assert_eq!(
+        interpreter
+            .interpret("xs.map(|x| x * 2).filter(|x| x > 2);")
+            .unwrap(),
+        super::list(vec![Value::Number(6.0), Value::Number(4.0)])
+    );
+    interpreter
+        .interpret("xs.for_each(|x, i| { total.sum = total.sum + x * i; });")
+        .unwrap();
+    assert_eq!(
+        interpreter.interpret("total.sum;").unwrap(),
+        Value::Number(5.0)
+    );
+    assert_eq!(
+        interpreter.interpret("xs.sort(|a, b| b - a);").unwrap(),
+        super::list(vec![
+            Value::Number(3.0),
+            Value::Number(2.0),
+            Value::Number(1.0)
+        ])
+    );
+    assert_eq!(
+        interpreter.interpret("xs.sort(); xs[0];").unwrap(),
+        Value::Number(1.0)
+    );
+    assert_eq!(
+        interpreter
+            .interpret("let ys = [2, 0 / 0, 1].sort(); [ys[0], ys[1], ys[2] == ys[2]];")
+            .unwrap(),
+        super::list(vec![
+            Value::Number(1.0),
+            Value::Number(2.0),
+            Value::Boolean(false)
+        ])
+    );
+    assert_eq!(
+        interpreter
+            .interpret("list(range(200)).sort(|a, b| random.int(-1, 1)).len();")
+            .unwrap(),
+        Value::Number(200.0)
+    );
+    assert!(interpreter.interpret("[1, 'a'].sort();").is_err());
+    assert!(interpreter
+        .interpret("xs.sort(|a, b| error('boom'));")
+        .is_err());
+    assert!(interpreter.interpret("xs.map(5);").is_err());
+}
@@ -0,0 +1,183 @@
+use super::{built_in, number_arg, object};
+use crate::{
+    enviroment::{Env, Value},
+    interpreter::IResult,
+    misc::NekoError,
+};
+use std::f64::consts;
+
+fn unary(name: &str, args: &[Value], function: fn(f64) -> f64) -> IResult {
+    Ok(Value::Number(function(number_arg(name, args, 0)?)))
+}
+
+fn fold(name: &str, args: &[Value], function: fn(f64, f64) -> f64) -> IResult {
+    let mut result = number_arg(name, args, 0)?;
+    for index in 1..args.len() {
+        result = function(result, number_arg(name, args, index)?);
+    }
+    Ok(Value::Number(result))
+}
+
+pub fn module(env: &Env) -> Value {
+    object(vec![
+        ("PI", Value::Number(consts::PI)),
+        ("E", Value::Number(consts::E)),
+        ("INF", Value::Number(f64::INFINITY)),
+        ("NAN", Value::Number(f64::NAN)),
+        (
+            "sin",
+            built_in("sin", |_, args| unary("math.sin", &args, f64::sin), env),
+        ),
+        (
+            "cos",
+            built_in("cos", |_, args| unary("math.cos", &args, f64::cos), env),
+        ),
+        (
+            "tan",
+            built_in("tan", |_, args| unary("math.tan", &args, f64::tan), env),
+        ),
+        (
+            "asin",
+            built_in("asin", |_, args| unary("math.asin", &args, f64::asin), env),
+        ),
+        (
+            "acos",
+            built_in("acos", |_, args| unary("math.acos", &args, f64::acos), env),
+        ),
+        (
+            "atan",
+            built_in("atan", |_, args| unary("math.atan", &args, f64::atan), env),
+        ),
+        (
+            "atan2",
+            built_in(
+                "atan2",
+                |_, args| {
+                    let y = number_arg("math.atan2", &args, 0)?;
+                    let x = number_arg("math.atan2", &args, 1)?;
+                    Ok(Value::Number(y.atan2(x)))
+                },
+                env,
+            ),
+        ),
+        (
+            "floor",
+            built_in(
+                "floor",
+                |_, args| unary("math.floor", &args, f64::floor),
+                env,
+            ),
+        ),
+        (
+            "ceil",
+            built_in("ceil", |_, args| unary("math.ceil", &args, f64::ceil), env),
+        ),
+        (
+            "round",
+            built_in(
+                "round",
+                |_, args| unary("math.round", &args, f64::round),
+                env,
+            ),
+        ),
+        (
+            "trunc",
+            built_in(
+                "trunc",
+                |_, args| unary("math.trunc", &args, f64::trunc),
+                env,
+            ),
+        ),
+        (
+            "abs",
+            built_in("abs", |_, args| unary("math.abs", &args, f64::abs), env),
+        ),
+        (
+            "sqrt",
+            built_in("sqrt", |_, args| unary("math.sqrt", &args, f64::sqrt), env),
+        ),
+        (
+            "exp",
+            built_in("exp", |_, args| unary("math.exp", &args, f64::exp), env),
+        ),
+        (
+            "log",
+            built_in(
+                "log",
+                |_, args| {
+                    let value = number_arg("math.log", &args, 0)?;
+                    match args.get(1) {
+                        Some(_) => Ok(Value::Number(value.log(number_arg("math.log", &args, 1)?))),
+                        None => Ok(Value::Number(value.ln())),
+                    }
+                },
+                env,
+            ),
+        ),
+        (
+            "min",
+            built_in("min", |_, args| fold("math.min", &args, f64::min), env),
+        ),
+        (
+            "max",
+            built_in("max", |_, args| fold("math.max", &args, f64::max), env),
+        ),
+        (
+            "clamp",
+            built_in(
+                "clamp",
+                |_, args| {
+                    let value = number_arg("math.clamp", &args, 0)?;
+                    let min = number_arg("math.clamp", &args, 1)?;
+                    let max = number_arg("math.clamp", &args, 2)?;
+                    // `f64::clamp` panics on NaN bounds, which `min > max` doesn't catch.
+                    if min.is_nan() || max.is_nan() || min > max {
+                        return Err(NekoError::TypeError(format!(
+                            "math.clamp expects min <= max, got {} and {}",
+                            min, max
+                        )));
+                    }
+                    Ok(Value::Number(value.clamp(min, max)))
+                },
+                env,
+            ),
+        ),
+    ])
+}
+
+#[test]
+fn should_compute_math_functions() {
+    use crate::interpreter::Interpreter;
+
+    let mut interpreter = Interpreter::new();
+    let cases = [
+        ("math.sqrt(16);", 4.0),
+        ("math.floor(2.7) + math.ceil(2.1) + math.round(2.5);", 8.0),
+        ("math.abs(-3);", 3.0),
+        ("math.min(4, 2, 8) + math.max(4, 2, 8);", 10.0),
+        ("math.clamp(15, 0, 10);", 10.0),
+        ("math.log(100, 10);", 2.0),
+        ("math.log(math.E);", 1.0),
+        ("math.cos(0) + math.sin(0);", 1.0),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(
+            interpreter.interpret(input).unwrap(),
+            Value::Number(expected),
+            "{}",
+            input
+        );
+    }
+    assert!(interpreter.interpret("math.sqrt('4');").is_err());
+    assert!(interpreter.interpret("math.clamp(1, 5, 0);").is_err());
+    assert!(interpreter
+        .interpret("math.clamp(1, math.NAN, 2);")
+        .is_err());
+    assert!(interpreter
+        .interpret("math.clamp(1, 0, math.NAN);")
+        .is_err());
+    assert_eq!(
+        interpreter.interpret("math.INF > 1000000;").unwrap(),
+        Value::Boolean(true)
+    );
+}
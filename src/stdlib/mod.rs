@@ -0,0 +1,137 @@
+use crate::{
+    enviroment::{Env, FunctionType, NativeFunction, Value},
+    interpreter::{IResult, Interpreter},
+    interpreter_option::InterpreterOptions,
+    misc::NekoError,
+};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+pub mod convert;
+mod fs;
+mod io;
+pub mod json;
+pub mod list;
+mod math;
+mod process;
+pub mod random;
+pub mod regex;
+pub mod string;
+pub mod time;
+
+pub use io::io_error;
+
+/// Returns every built-in module and global function as `(name, value)`.
+/// Modules are defined whatever the policy, the interpreter hides the ones
+/// an evaluation's policy does not allow.
+pub fn globals(env: &Env, options: &InterpreterOptions) -> Vec<(&'static str, Value)> {
+    let mut globals = vec![
+        ("math", math::module(env)),
+        ("json", json::module(env)),
+        ("fs", fs::module(env)),
+        ("time", time::module(env)),
+        ("random", random::module(env)),
+        ("regex", regex::module(env)),
+        ("process", process::module(env, &options.args)),
+    ];
+    globals.extend(io::functions(env));
+    globals.extend(convert::functions(env));
+    globals
+        .into_iter()
+        .map(|(name, value)| match module_of(name) {
+            Some(module) => (name, guard(module, value)),
+            None => (name, value),
+        })
+        .collect()
+}
+
+/// Makes the functions of `module` check the policy when called, so ones
+/// copied out of the module earlier fail once it is no longer allowed.
+fn guard(module: &'static str, value: Value) -> Value {
+    match value {
+        Value::Function(FunctionType::BuiltIn { name, function }, env) => {
+            let function: NativeFunction = Rc::new(move |interpreter, args| {
+                interpreter.check_module(module)?;
+                function(interpreter, args)
+            });
+            Value::Function(FunctionType::BuiltIn { name, function }, env)
+        }
+        Value::Object(obj) => {
+            for value in obj.borrow_mut().values_mut() {
+                **value = guard(module, std::mem::replace(&mut **value, Value::None));
+            }
+            Value::Object(obj)
+        }
+        value => value,
+    }
+}
+
+/// The module out of `policy::MODULES` a built-in global belongs to, `None`
+/// for globals every policy allows.
+pub fn module_of(name: &str) -> Option<&'static str> {
+    match name {
+        "math" => Some("math"),
+        "json" => Some("json"),
+        "fs" => Some("fs"),
+        "time" => Some("time"),
+        "random" => Some("random"),
+        "regex" => Some("regex"),
+        "process" => Some("process"),
+        "input" | "read_line" | "read_all" | "lines" => Some("io"),
+        _ => None,
+    }
+}
+
+fn object(entries: Vec<(&str, Value)>) -> Value {
+    let values: HashMap<String, Box<Value>> = entries
+        .into_iter()
+        .map(|(key, value)| (String::from(key), Box::new(value)))
+        .collect();
+    Value::Object(Rc::new(RefCell::new(values)))
+}
+
+fn built_in<F>(name: &str, function: F, env: &Env) -> Value
+where
+    F: Fn(&mut Interpreter, Vec<Value>) -> IResult + 'static,
+{
+    Value::Function(FunctionType::built_in(name, function), Rc::clone(env))
+}
+
+fn number_arg(name: &str, args: &[Value], index: usize) -> Result<f64, NekoError> {
+    match args.get(index) {
+        Some(Value::Number(num)) => Ok(*num),
+        Some(other) => Err(NekoError::TypeError(format!(
+            "{} expects a Number as argument {}, got {}",
+            name,
+            index + 1,
+            other
+        ))),
+        None => Err(NekoError::TypeError(format!(
+            "{} expects at least {} arguments, got {}",
+            name,
+            index + 1,
+            args.len()
+        ))),
+    }
+}
+
+fn string_arg<'a>(name: &str, args: &'a [Value], index: usize) -> Result<&'a str, NekoError> {
+    match args.get(index) {
+        Some(Value::String(string)) => Ok(string),
+        Some(other) => Err(NekoError::TypeError(format!(
+            "{} expects a String as argument {}, got {}",
+            name,
+            index + 1,
+            other
+        ))),
+        None => Err(NekoError::TypeError(format!(
+            "{} expects at least {} arguments, got {}",
+            name,
+            index + 1,
+            args.len()
+        ))),
+    }
+}
+
+fn list(values: Vec<Value>) -> Value {
+    Value::List(Rc::new(RefCell::new(values)))
+}
@@ -0,0 +1,85 @@
+use super::{built_in, list, number_arg, object};
+use crate::{
+    enviroment::{Env, Value},
+    misc::NekoError,
+};
+use std::env;
+
+pub fn module(env: &Env, args: &[String]) -> Value {
+    // A read-only snapshot of the environment, variables that are not valid
+    // UTF-8 are converted lossily instead of failing.
+    let variables = env::vars_os()
+        .map(|(key, value)| {
+            (
+                key.to_string_lossy().into_owned(),
+                Value::String(value.to_string_lossy().into_owned()),
+            )
+        })
+        .collect::<Vec<(String, Value)>>();
+
+    object(vec![
+        (
+            "args",
+            list(args.iter().cloned().map(Value::String).collect()),
+        ),
+        (
+            "env",
+            object(
+                variables
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.clone()))
+                    .collect(),
+            ),
+        ),
+        (
+            "exit",
+            built_in(
+                "exit",
+                |_, args| {
+                    let code = match args.first() {
+                        Some(_) => number_arg("process.exit", &args, 0)?,
+                        None => 0.0,
+                    };
+                    if code.fract() != 0.0 || code < i32::MIN as f64 || code > i32::MAX as f64 {
+                        return Err(NekoError::TypeError(format!(
+                            "process.exit expects an integer exit code, got {}",
+                            code
+                        )));
+                    }
+                    Err(NekoError::Exit(code as i32))
+                },
+                env,
+            ),
+        ),
+    ])
+}
+
+#[test]
+fn should_expose_args_and_exit() {
+    use crate::{interpreter::Interpreter, interpreter_option::InterpreterOptions};
+
+    let mut options = InterpreterOptions::new();
+    options.args = vec![String::from("a"), String::from("b")];
+    let mut interpreter = Interpreter::with_options(options);
+    assert_eq!(
+        interpreter.interpret("process.args.len();").unwrap(),
+        Value::Number(2.0)
+    );
+    assert_eq!(
+        interpreter.interpret("process.args[1];").unwrap(),
+        Value::String(String::from("b"))
+    );
+    assert_eq!(
+        interpreter
+            .interpret("function run() { process.exit(3); print('unreachable'); } run();")
+            .unwrap_err(),
+        NekoError::Exit(3)
+    );
+    assert!(interpreter.interpret("process.exit(1.5);").is_err());
+    assert!(interpreter.interpret("process.env.NEKO = 'x';").is_err());
+    assert!(interpreter.interpret("process.env['NEKO'] = 'x';").is_err());
+    assert_eq!(
+        interpreter.interpret("process.env.NEKO;").unwrap(),
+        Value::None
+    );
+}
@@ -0,0 +1,203 @@
+use super::{built_in, number_arg, object};
+use crate::{
+    enviroment::{Env, Value},
+    misc::NekoError,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A splitmix64 generator, small and stable across platforms and releases so
+/// that a seed always reproduces the same sequence.
+#[derive(Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_nanos() as u64)
+        });
+        Self { state: seed }
+    }
+
+    pub fn seed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A float in `[0, 1)`.
+    fn next_float(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// An integer in `[0, bound)`, without modulo bias. A bound of 0 stands for
+    /// 2^64, the full range.
+    fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return self.next_u64();
+        }
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+/// Largest integer a number holds exactly, 2^53.
+const MAX_SAFE_INTEGER: f64 = 9007199254740992.0;
+
+fn integer_arg(name: &str, args: &[Value], index: usize) -> Result<i64, NekoError> {
+    let num = number_arg(name, args, index)?;
+    if num.fract() != 0.0 || !num.is_finite() || num.abs() > MAX_SAFE_INTEGER {
+        return Err(NekoError::TypeError(format!(
+            "{} expects an integer between -2^53 and 2^53 as argument {}, got {}",
+            name,
+            index + 1,
+            num
+        )));
+    }
+    Ok(num as i64)
+}
+
+fn list_arg(name: &str, args: &[Value]) -> Result<Value, NekoError> {
+    match args.first() {
+        Some(list @ Value::List(_)) => Ok(list.clone()),
+        Some(other) => Err(NekoError::TypeError(format!(
+            "{} expects a List, got {}",
+            name, other
+        ))),
+        None => Err(NekoError::TypeError(format!("{} expects a List", name))),
+    }
+}
+
+pub fn module(env: &Env) -> Value {
+    object(vec![
+        (
+            "seed",
+            built_in(
+                "seed",
+                |interpreter, args| {
+                    let seed = integer_arg("random.seed", &args, 0)?;
+                    interpreter.random().seed(seed as u64);
+                    Ok(Value::None)
+                },
+                env,
+            ),
+        ),
+        (
+            "float",
+            built_in(
+                "float",
+                |interpreter, _| Ok(Value::Number(interpreter.random().next_float())),
+                env,
+            ),
+        ),
+        (
+            "int",
+            built_in(
+                "int",
+                |interpreter, args| {
+                    let min = integer_arg("random.int", &args, 0)?;
+                    let max = integer_arg("random.int", &args, 1)?;
+                    if min > max {
+                        return Err(NekoError::TypeError(format!(
+                            "random.int expects min <= max, got {} and {}",
+                            min, max
+                        )));
+                    }
+                    let span = (max as i128 - min as i128 + 1) as u64;
+                    let offset = interpreter.random().below(span);
+                    Ok(Value::Number((min as i128 + offset as i128) as f64))
+                },
+                env,
+            ),
+        ),
+        (
+            "choice",
+            built_in(
+                "choice",
+                |interpreter, args| match list_arg("random.choice", &args)? {
+                    Value::List(list) => {
+                        let list = list.borrow();
+                        if list.is_empty() {
+                            return Err(NekoError::TypeError(String::from(
+                                "random.choice expects a non-empty List",
+                            )));
+                        }
+                        let index = interpreter.random().below(list.len() as u64) as usize;
+                        Ok(list[index].clone())
+                    }
+                    _ => unreachable!(),
+                },
+                env,
+            ),
+        ),
+        (
+            "shuffle",
+            built_in(
+                "shuffle",
+                |interpreter, args| {
+                    let list = list_arg("random.shuffle", &args)?;
+                    interpreter.check_mutable(&list)?;
+                    match list {
+                        Value::List(list) => {
+                            let mut list = list.borrow_mut();
+                            for index in (1..list.len()).rev() {
+                                let other = interpreter.random().below(index as u64 + 1) as usize;
+                                list.swap(index, other);
+                            }
+                            Ok(Value::None)
+                        }
+                        _ => unreachable!(),
+                    }
+                },
+                env,
+            ),
+        ),
+    ])
+}
+
+#[test]
+fn should_reproduce_sequences_from_a_seed() {
+    use crate::interpreter::Interpreter;
+
+    let mut interpreter = Interpreter::new();
+    let script = "random.seed(42);
+        let xs = [1, 2, 3, 4, 5];
+        random.shuffle(xs);
+        [random.int(1, 6), random.float(), random.choice(xs), xs];";
+    let first = interpreter.interpret(script).unwrap();
+    assert_eq!(Interpreter::new().interpret(script).unwrap(), first);
+
+    interpreter.interpret("random.seed(7);").unwrap();
+    for _ in 0..100 {
+        match interpreter.interpret("random.int(-2, 2);").unwrap() {
+            Value::Number(num) => assert!((-2.0..=2.0).contains(&num) && num.fract() == 0.0),
+            other => panic!("Expected a number, got {}", other),
+        }
+    }
+    assert!(interpreter.interpret("random.int(3, 1);").is_err());
+    assert!(interpreter.interpret("random.choice([]);").is_err());
+    assert!(interpreter
+        .interpret("random.int(-(10 ** 19), 10 ** 19);")
+        .is_err());
+    match interpreter
+        .interpret("random.int(-(2 ** 53), 2 ** 53);")
+        .unwrap()
+    {
+        Value::Number(num) => assert!(num.abs() <= 9007199254740992.0),
+        other => panic!("Expected a number, got {}", other),
+    }
+}
@@ -0,0 +1,138 @@
+use super::{built_in, list, object, string_arg};
+use crate::{
+    enviroment::{Env, Value},
+    interpreter::{IResult, Interpreter, Method, VALUE_SIZE},
+    misc::NekoError,
+};
+use regex::Regex;
+use std::rc::Rc;
+
+fn receiver(value: &Value) -> &Regex {
+    match value {
+        Value::Regex(regex) => regex,
+        _ => unreachable!(),
+    }
+}
+
+/// Returns an object of the named groups of the first match, `none` without a match.
+/// Groups that did not take part in the match are `none`.
+fn captures(interpreter: &mut Interpreter, regex: Value, args: Vec<Value>) -> IResult {
+    let regex = receiver(&regex);
+    let text = string_arg("captures", &args, 0)?;
+    let captures = match regex.captures(text) {
+        Some(captures) => captures,
+        None => return Ok(Value::None),
+    };
+    let mut groups = vec![];
+    for name in regex.capture_names().flatten() {
+        let group = captures.name(name).map(|group| group.as_str());
+        interpreter.allocate(name.len() + VALUE_SIZE + group.map_or(0, str::len))?;
+        let value = group.map_or(Value::None, |group| Value::String(String::from(group)));
+        groups.push((name, value));
+    }
+    Ok(interpreter.created(object(groups)))
+}
+
+/// Replaces every match, `$1` and `$name` in the replacement refer to groups.
+/// The result is charged as it grows, as expanded groups make its size unknown.
+fn replace(interpreter: &mut Interpreter, regex: Value, args: Vec<Value>) -> IResult {
+    let regex = receiver(&regex);
+    let text = string_arg("replace", &args, 0)?;
+    let replacement = string_arg("replace", &args, 1)?;
+    let mut result = String::new();
+    let mut last = 0;
+    for captures in regex.captures_iter(text) {
+        let found = captures.get(0).expect("group 0 is the whole match");
+        let len = result.len();
+        result.push_str(&text[last..found.start()]);
+        captures.expand(replacement, &mut result);
+        interpreter.allocate(result.len() - len)?;
+        last = found.end();
+    }
+    interpreter.allocate(text.len() - last)?;
+    result.push_str(&text[last..]);
+    Ok(Value::String(result))
+}
+
+pub fn method(name: &str) -> Option<Method> {
+    match name {
+        "test" => Some(|_, regex, args| {
+            let text = string_arg("test", &args, 0)?;
+            Ok(Value::Boolean(receiver(&regex).is_match(text)))
+        }),
+        "find" => Some(|interpreter, regex, args| {
+            let text = string_arg("find", &args, 0)?;
+            match receiver(&regex).find(text) {
+                Some(found) => {
+                    interpreter.allocate(found.as_str().len())?;
+                    Ok(Value::String(String::from(found.as_str())))
+                }
+                None => Ok(Value::None),
+            }
+        }),
+        "find_all" => Some(|interpreter, regex, args| {
+            let text = string_arg("find_all", &args, 0)?;
+            let mut found = vec![];
+            for matched in receiver(&regex).find_iter(text) {
+                interpreter.allocate(VALUE_SIZE + matched.as_str().len())?;
+                found.push(Value::String(String::from(matched.as_str())));
+            }
+            Ok(interpreter.created(list(found)))
+        }),
+        "replace" => Some(replace),
+        "captures" => Some(captures),
+        _ => None,
+    }
+}
+
+pub fn module(env: &Env) -> Value {
+    object(vec![(
+        "new",
+        built_in(
+            "new",
+            |_, args| {
+                let pattern = string_arg("regex.new", &args, 0)?;
+                Regex::new(pattern)
+                    .map(|regex| Value::Regex(Rc::new(regex)))
+                    .map_err(|err| NekoError::TypeError(format!("Invalid regex: {}", err)))
+            },
+            env,
+        ),
+    )])
+}
+
+#[test]
+fn should_match_regular_expressions() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .interpret(r"let date = regex.new('(?P<year>\d{4})-(?P<month>\d{2})(-(?P<day>\d{2}))?');")
+        .unwrap();
+    let cases = [
+        ("date.test('on 2021-03');", Value::Boolean(true)),
+        ("date.test('on 21-03');", Value::Boolean(false)),
+        (
+            "date.find('from 2020-01 to 2021-02');",
+            Value::String(String::from("2020-01")),
+        ),
+        ("date.find('never');", Value::None),
+        (
+            "date.find_all('2020-01, 2021-02-03').len();",
+            Value::Number(2.0),
+        ),
+        (
+            "date.replace('2021-03-04', '$day/$month/$year');",
+            Value::String(String::from("04/03/2021")),
+        ),
+        (
+            "date.captures('2021-03').month;",
+            Value::String(String::from("03")),
+        ),
+        ("date.captures('2021-03').day;", Value::None),
+        ("date.captures('nothing');", Value::None),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(interpreter.interpret(input).unwrap(), expected, "{}", input);
+    }
+    assert!(interpreter.interpret("regex.new('(');").is_err());
+    assert!(interpreter.interpret("date.test(1);").is_err());
+}
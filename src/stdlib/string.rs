@@ -0,0 +1,195 @@
+use super::{list, number_arg, string_arg};
+use crate::{
+    enviroment::Value,
+    interpreter::{IResult, Interpreter, Method, VALUE_SIZE},
+    misc::NekoError,
+};
+
+fn receiver(value: &Value) -> &str {
+    match value {
+        Value::String(string) => string,
+        _ => unreachable!(),
+    }
+}
+
+/// Converts a character index argument, counting from the end when negative.
+fn char_index(name: &str, args: &[Value], index: usize, len: usize) -> Result<usize, NekoError> {
+    let position = number_arg(name, args, index)?;
+    if position.fract() != 0.0 {
+        return Err(NekoError::TypeError(format!(
+            "{} expects an integer index, got {}",
+            name, position
+        )));
+    }
+    let position = if position < 0.0 {
+        len as f64 + position
+    } else {
+        position
+    };
+    Ok(position.max(0.0).min(len as f64) as usize)
+}
+
+fn len(_: &mut Interpreter, string: Value, _: Vec<Value>) -> IResult {
+    Ok(Value::Number(receiver(&string).chars().count() as f64))
+}
+
+fn split(interpreter: &mut Interpreter, string: Value, args: Vec<Value>) -> IResult {
+    let string = receiver(&string);
+    let parts: Vec<Value> = match args.first() {
+        None => string
+            .split_whitespace()
+            .map(|part| Value::String(String::from(part)))
+            .collect(),
+        Some(_) => {
+            let separator = string_arg("split", &args, 0)?;
+            if separator.is_empty() {
+                return Err(NekoError::TypeError(String::from(
+                    "split expects a non-empty separator",
+                )));
+            }
+            string
+                .split(separator)
+                .map(|part| Value::String(String::from(part)))
+                .collect()
+        }
+    };
+    interpreter.allocate(string.len() + parts.len() * VALUE_SIZE)?;
+    Ok(interpreter.created(list(parts)))
+}
+
+fn slice(interpreter: &mut Interpreter, string: Value, args: Vec<Value>) -> IResult {
+    let chars: Vec<char> = receiver(&string).chars().collect();
+    let start = char_index("slice", &args, 0, chars.len())?;
+    let end = match args.get(1) {
+        Some(_) => char_index("slice", &args, 1, chars.len())?,
+        None => chars.len(),
+    };
+    let sliced = chars[start..end.max(start)].iter().collect::<String>();
+    interpreter.allocate(sliced.len())?;
+    Ok(Value::String(sliced))
+}
+
+/// Returns the character index of the first occurrence, or `none` when absent.
+fn find(_: &mut Interpreter, string: Value, args: Vec<Value>) -> IResult {
+    let string = receiver(&string);
+    let pattern = string_arg("find", &args, 0)?;
+    Ok(match string.find(pattern) {
+        Some(byte_index) => Value::Number(string[..byte_index].chars().count() as f64),
+        None => Value::None,
+    })
+}
+
+fn pad_start(interpreter: &mut Interpreter, string: Value, args: Vec<Value>) -> IResult {
+    let string = receiver(&string);
+    let width = number_arg("pad_start", &args, 0)?;
+    if !width.is_finite() || width.fract() != 0.0 {
+        return Err(NekoError::TypeError(format!(
+            "pad_start expects an integer width, got {}",
+            width
+        )));
+    }
+    let fill = match args.get(1) {
+        Some(_) => string_arg("pad_start", &args, 1)?,
+        None => " ",
+    };
+    let len = string.chars().count();
+    if fill.is_empty() || width <= len as f64 {
+        return Ok(Value::String(String::from(string)));
+    }
+    let padding_len = width as usize - len;
+    interpreter.allocate(string.len() + padding_len.saturating_mul(fill.len()))?;
+    let padding: String = fill.chars().cycle().take(padding_len).collect();
+    Ok(Value::String(padding + string))
+}
+
+pub fn method(name: &str) -> Option<Method> {
+    match name {
+        "len" => Some(len),
+        "split" => Some(split),
+        "trim" => Some(|interpreter, string, _| {
+            let trimmed = receiver(&string).trim();
+            interpreter.allocate(trimmed.len())?;
+            Ok(Value::String(String::from(trimmed)))
+        }),
+        "upper" => Some(|interpreter, string, _| {
+            interpreter.allocate(receiver(&string).len())?;
+            Ok(Value::String(receiver(&string).to_uppercase()))
+        }),
+        "lower" => Some(|interpreter, string, _| {
+            interpreter.allocate(receiver(&string).len())?;
+            Ok(Value::String(receiver(&string).to_lowercase()))
+        }),
+        "replace" => Some(|interpreter, string, args| {
+            let string = receiver(&string);
+            let from = string_arg("replace", &args, 0)?;
+            let to = string_arg("replace", &args, 1)?;
+            let matches = if from.is_empty() {
+                string.chars().count() + 1
+            } else {
+                string.matches(from).count()
+            };
+            interpreter.allocate(string.len() + matches.saturating_mul(to.len()))?;
+            Ok(Value::String(string.replace(from, to)))
+        }),
+        "contains" => Some(|_, string, args| {
+            let pattern = string_arg("contains", &args, 0)?;
+            Ok(Value::Boolean(receiver(&string).contains(pattern)))
+        }),
+        "starts_with" => Some(|_, string, args| {
+            let pattern = string_arg("starts_with", &args, 0)?;
+            Ok(Value::Boolean(receiver(&string).starts_with(pattern)))
+        }),
+        "ends_with" => Some(|_, string, args| {
+            let pattern = string_arg("ends_with", &args, 0)?;
+            Ok(Value::Boolean(receiver(&string).ends_with(pattern)))
+        }),
+        "find" => Some(find),
+        "slice" => Some(slice),
+        "chars" => Some(|interpreter, string, _| {
+            interpreter.allocate(receiver(&string).chars().count() * (VALUE_SIZE + 4))?;
+            Ok(interpreter.created(list(
+                receiver(&string)
+                    .chars()
+                    .map(|c| Value::String(c.to_string()))
+                    .collect(),
+            )))
+        }),
+        "pad_start" => Some(pad_start),
+        _ => None,
+    }
+}
+
+#[test]
+fn should_call_string_methods() {
+    let mut interpreter = Interpreter::new();
+    let cases = [
+        ("'héllo'.len();", Value::Number(5.0)),
+        ("'  hi '.trim();", Value::String(String::from("hi"))),
+        (
+            "'Neko'.upper() + 'Neko'.lower();",
+            Value::String(String::from("NEKOneko")),
+        ),
+        (
+            "'a-b-a'.replace('a', 'c');",
+            Value::String(String::from("c-b-c")),
+        ),
+        ("'neko'.contains('ek');", Value::Boolean(true)),
+        ("'neko'.starts_with('ko');", Value::Boolean(false)),
+        ("'héllo'.find('l');", Value::Number(2.0)),
+        ("'neko'.find('z');", Value::None),
+        ("'neko'.slice(1, 3);", Value::String(String::from("ek"))),
+        ("'neko'.slice(-2);", Value::String(String::from("ko"))),
+        ("'7'.pad_start(3, '0');", Value::String(String::from("007"))),
+        ("'a,b,c'.split(',').len();", Value::Number(3.0)),
+        ("' a  b '.split()[1];", Value::String(String::from("b"))),
+        ("'ab'.chars()[1];", Value::String(String::from("b"))),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(interpreter.interpret(input).unwrap(), expected, "{}", input);
+    }
+    assert!(interpreter.interpret("'neko'.reverse();").is_err());
+    assert!(interpreter.interpret("'neko'.contains(1);").is_err());
+    assert!(interpreter.interpret("'ab'.pad_start(0 / 0);").is_err());
+    assert!(interpreter.interpret("'ab'.pad_start(math.INF);").is_err());
+    assert!(interpreter.interpret("'ab'.pad_start(2.5);").is_err());
+}
@@ -0,0 +1,236 @@
+use super::{built_in, number_arg, object, string_arg};
+use crate::{
+    enviroment::{Env, Value},
+    misc::NekoError,
+};
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, LocalResult, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc,
+};
+use std::{
+    fmt::Write,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Milliseconds `time.sleep` blocks for before checking for interrupts.
+const SLEEP_SLICE: f64 = 50.0;
+
+/// The clock behind the `time` module. A fake clock only moves on `time.sleep`,
+/// which makes scripts that read the time reproducible.
+#[derive(Debug)]
+pub enum Clock {
+    System { start: Instant },
+    Fake { now: f64, elapsed: f64 },
+}
+
+impl Clock {
+    pub fn new(fake_clock: Option<f64>) -> Self {
+        match fake_clock {
+            Some(now) => Clock::Fake { now, elapsed: 0.0 },
+            None => Clock::System {
+                start: Instant::now(),
+            },
+        }
+    }
+
+    /// Milliseconds since the Unix epoch.
+    fn now(&self) -> f64 {
+        match self {
+            Clock::System { .. } => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0.0, |duration| duration.as_secs_f64() * 1000.0),
+            Clock::Fake { now, .. } => *now,
+        }
+    }
+
+    /// Milliseconds since the clock was created, never going backwards.
+    fn elapsed(&self) -> f64 {
+        match self {
+            Clock::System { start } => start.elapsed().as_secs_f64() * 1000.0,
+            Clock::Fake { elapsed, .. } => *elapsed,
+        }
+    }
+
+    fn sleep(&mut self, ms: f64) {
+        match self {
+            Clock::System { .. } => thread::sleep(Duration::from_secs_f64(ms / 1000.0)),
+            Clock::Fake { now, elapsed } => {
+                *now += ms;
+                *elapsed += ms;
+            }
+        }
+    }
+}
+
+fn format(timestamp: f64, format: Option<&str>) -> Result<String, NekoError> {
+    let date = match Utc.timestamp_millis_opt(timestamp as i64) {
+        LocalResult::Single(date) if timestamp.is_finite() => date,
+        _ => {
+            return Err(NekoError::TypeError(format!(
+                "{} is not a valid timestamp",
+                timestamp
+            )))
+        }
+    };
+    match format {
+        None => Ok(date.to_rfc3339_opts(SecondsFormat::Millis, true)),
+        Some(format) => {
+            if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                return Err(NekoError::TypeError(format!(
+                    "Invalid date format '{}'",
+                    format
+                )));
+            }
+            let mut output = String::new();
+            write!(output, "{}", date.format(format)).map_err(|_| {
+                NekoError::TypeError(format!("Cannot format date with '{}'", format))
+            })?;
+            Ok(output)
+        }
+    }
+}
+
+fn parse(text: &str, format: Option<&str>) -> Result<f64, NekoError> {
+    let parsed = match format {
+        None => DateTime::parse_from_rfc3339(text).map(|date| date.timestamp_millis()),
+        Some(format) => DateTime::parse_from_str(text, format)
+            .map(|date| date.timestamp_millis())
+            .or_else(|_| {
+                NaiveDateTime::parse_from_str(text, format).map(|date| date.timestamp_millis())
+            })
+            .or_else(|_| {
+                NaiveDate::parse_from_str(text, format)
+                    .map(|date| date.and_hms(0, 0, 0).timestamp_millis())
+            }),
+    };
+    parsed
+        .map(|millis| millis as f64)
+        .map_err(|err| NekoError::TypeError(format!("Cannot parse date '{}': {}", text, err)))
+}
+
+pub fn module(env: &Env) -> Value {
+    object(vec![
+        (
+            "now",
+            built_in(
+                "now",
+                |interpreter, _| Ok(Value::Number(interpreter.clock().now())),
+                env,
+            ),
+        ),
+        (
+            "clock",
+            built_in(
+                "clock",
+                |interpreter, _| Ok(Value::Number(interpreter.clock().elapsed())),
+                env,
+            ),
+        ),
+        (
+            "sleep",
+            built_in(
+                "sleep",
+                |interpreter, args| {
+                    let ms = number_arg("time.sleep", &args, 0)?;
+                    if !ms.is_finite() || ms < 0.0 {
+                        return Err(NekoError::TypeError(format!(
+                            "time.sleep expects a non-negative duration, got {}",
+                            ms
+                        )));
+                    }
+                    // The system clock sleeps in slices so an interrupt does not
+                    // wait for the whole duration, a fake clock advances at once.
+                    let slice = match interpreter.clock() {
+                        Clock::System { .. } => SLEEP_SLICE,
+                        Clock::Fake { .. } => ms,
+                    };
+                    let mut remaining = ms;
+                    while remaining > 0.0 {
+                        interpreter.check_interrupt()?;
+                        let slice = remaining.min(slice);
+                        interpreter.clock().sleep(slice);
+                        remaining -= slice;
+                    }
+                    Ok(Value::None)
+                },
+                env,
+            ),
+        ),
+        (
+            "format",
+            built_in(
+                "format",
+                |_, args| {
+                    let timestamp = number_arg("time.format", &args, 0)?;
+                    let format_string = match args.get(1) {
+                        Some(_) => Some(string_arg("time.format", &args, 1)?),
+                        None => None,
+                    };
+                    format(timestamp, format_string).map(Value::String)
+                },
+                env,
+            ),
+        ),
+        (
+            "parse",
+            built_in(
+                "parse",
+                |_, args| {
+                    let text = string_arg("time.parse", &args, 0)?;
+                    let format_string = match args.get(1) {
+                        Some(_) => Some(string_arg("time.parse", &args, 1)?),
+                        None => None,
+                    };
+                    parse(text, format_string).map(Value::Number)
+                },
+                env,
+            ),
+        ),
+    ])
+}
+
+#[test]
+fn should_use_the_fake_clock() {
+    use crate::{interpreter::Interpreter, interpreter_option::InterpreterOptions};
+
+    let mut options = InterpreterOptions::new();
+    options.fake_clock = Some(1_600_000_000_000.0);
+    let mut interpreter = Interpreter::with_options(options);
+    interpreter.interpret("let start = time.clock();").unwrap();
+    interpreter.interpret("time.sleep(1500);").unwrap();
+    assert_eq!(
+        interpreter.interpret("time.clock() - start;").unwrap(),
+        Value::Number(1500.0)
+    );
+    assert_eq!(
+        interpreter.interpret("time.format(time.now());").unwrap(),
+        Value::String(String::from("2020-09-13T12:26:41.500Z"))
+    );
+    interpreter.interpret("time.sleep(10 ** 15);").unwrap();
+    assert_eq!(
+        interpreter.interpret("time.clock() - start;").unwrap(),
+        Value::Number(1_000_000_000_001_500.0)
+    );
+}
+
+#[test]
+fn should_format_and_parse_dates() {
+    use crate::interpreter::Interpreter;
+
+    let mut interpreter = Interpreter::new();
+    assert_eq!(
+        interpreter
+            .interpret("time.parse('2021-03-04T05:06:07.250+01:00');")
+            .unwrap(),
+        Value::Number(1_614_830_767_250.0)
+    );
+    assert_eq!(
+        interpreter
+            .interpret("time.format(time.parse('04/03/2021', '%d/%m/%Y'), '%Y-%m-%d %H:%M');")
+            .unwrap(),
+        Value::String(String::from("2021-03-04 00:00"))
+    );
+    assert!(interpreter.interpret("time.parse('yesterday');").is_err());
+    assert!(interpreter.interpret("time.format(0, '%Q');").is_err());
+}
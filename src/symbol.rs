@@ -1,24 +1,21 @@
-#[derive(Debug, Clone)]
-pub struct VarSymbol {
-    pub name: String,
-    pub symbol_type: TypeSymbol,
-}
+use crate::ast::EnumVariant;
 
 #[derive(Debug, Clone)]
-pub struct FunctionSymbol {
-    pub name: String,
-    pub param: Vec<String>,
+pub struct EnumSymbol {
+    pub variants: Vec<EnumVariant>,
 }
 
-#[derive(Debug, Clone)]
-pub enum TypeSymbol {
-    Number,
-    Unknown,
+impl EnumSymbol {
+    pub fn variant(&self, name: &str) -> Option<&EnumVariant> {
+        self.variants.iter().find(|variant| variant.name == name)
+    }
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum Symbol {
-    VarSymbol(VarSymbol),
+    VarSymbol,
     BuiltInSymbol(String),
-    FunctionSymbol(FunctionSymbol),
+    FunctionSymbol,
+    EnumSymbol(EnumSymbol),
 }
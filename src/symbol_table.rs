@@ -4,20 +4,14 @@ use std::{cell::RefCell, collections::HashMap, rc::Rc};
 #[derive(Debug)]
 pub struct SymbolTable {
     pub symbols: HashMap<String, Symbol>,
-    pub scope_name: String,
     pub scope_level: u64,
     pub enclosing_scope: Option<Rc<RefCell<SymbolTable>>>,
 }
 
 impl SymbolTable {
-    pub fn new(
-        scope_name: &str,
-        scope_level: u64,
-        enclosing_scope: Option<Rc<RefCell<SymbolTable>>>,
-    ) -> Self {
+    pub fn new(scope_level: u64, enclosing_scope: Option<Rc<RefCell<SymbolTable>>>) -> Self {
         Self {
             symbols: HashMap::new(),
-            scope_name: scope_name.to_string(),
             scope_level,
             enclosing_scope,
         }
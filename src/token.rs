@@ -13,11 +13,14 @@ pub enum Token {
     RParen,
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
     Comma,
     EndOfFile,
     Semicolon,
     Colon,
     Dot,
+    FatArrow,
     Unknown,
 }
 
@@ -48,6 +51,7 @@ pub enum Operator {
 
     DoublePipe,
     Pipe,
+    Pipeline,
 
     Not,
 }
@@ -59,6 +63,12 @@ pub enum Keyword {
     And,
     Or,
     None,
+    Match,
+    If,
+    Enum,
+    For,
+    In,
+    Yield,
 }
 
 impl fmt::Display for Token {